@@ -1,17 +1,60 @@
 #![allow(dead_code)]
 
 use crate::config::Config;
+use crate::db::entities::incident::Model as IncidentModel;
 use crate::db::entities::server::Model as ServerModel;
+use crate::db::entities::server_group::Model as ServerGroupModel;
+use crate::db::entities::user_role::Role;
 use crate::error::Result;
 use crate::monitor;
+use crate::monitor::flap::Thresholds;
 use crate::monitor::tasks;
+use crate::monitor::tls::CertificateInfo;
+use crate::monitor::{AddressOutcome, CheckOutcome, MonitorControl, MonitorScope};
+use crate::notify::email::{EmailNotifier, Mailer};
+use crate::notify::webhook::WebhookNotifier;
+use crate::notify::{Notifier, StatusEvent};
+use crate::services::check::CheckService;
 use crate::services::group::GroupService;
-use crate::services::server::ServerService;
+use crate::services::incident::IncidentService;
+use crate::services::role::RoleService;
+use crate::services::server::{NewServer, ServerService};
+use crate::services::settings::SettingsService;
+use crate::services::subscription::SubscriptionService;
+use chrono::{DateTime, Utc};
+use futures::{stream, StreamExt};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use teloxide::prelude::*;
-use tokio::sync::Mutex;
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{timeout_at, Instant};
+
+/// A running monitor task and the switches that steer it.
+struct MonitorHandle {
+    task: JoinHandle<()>,
+    control: watch::Sender<MonitorControl>,
+    started_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MonitorInfo {
+    pub scope: MonitorScope,
+    pub control: MonitorControl,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MonitorStart {
+    Started,
+    Resumed,
+    AlreadyRunning,
+    /// Another monitor of the chat already covers some of these servers: all
+    /// servers and a group, or two groups sharing a server, would check them twice.
+    Overlaps(MonitorScope),
+}
 
 #[derive(Clone)]
 pub struct ServerFatherBot {
@@ -19,22 +62,41 @@ pub struct ServerFatherBot {
     pub config: Config,
     server_service: ServerService,
     group_service: GroupService,
-    chat_ids: Arc<Mutex<HashMap<i64, bool>>>,
+    check_service: CheckService,
+    settings_service: SettingsService,
+    subscription_service: SubscriptionService,
+    role_service: RoleService,
+    incident_service: IncidentService,
+    mailer: Option<Mailer>,
+    monitors: Arc<Mutex<HashMap<(ChatId, MonitorScope), MonitorHandle>>>,
 }
 
 impl ServerFatherBot {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         bot: Bot,
         config: Config,
         server_service: ServerService,
         group_service: GroupService,
+        check_service: CheckService,
+        settings_service: SettingsService,
+        subscription_service: SubscriptionService,
+        role_service: RoleService,
+        incident_service: IncidentService,
+        mailer: Option<Mailer>,
     ) -> Self {
         Self {
             bot,
             config,
             server_service,
             group_service,
-            chat_ids: Arc::new(Mutex::new(HashMap::new())),
+            check_service,
+            settings_service,
+            subscription_service,
+            role_service,
+            incident_service,
+            mailer,
+            monitors: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -46,6 +108,27 @@ impl ServerFatherBot {
         &self.group_service
     }
 
+    pub fn check_service(&self) -> &CheckService {
+        &self.check_service
+    }
+
+    pub fn settings_service(&self) -> &SettingsService {
+        &self.settings_service
+    }
+
+    pub fn role_service(&self) -> &RoleService {
+        &self.role_service
+    }
+
+    pub fn incident_service(&self) -> &IncidentService {
+        &self.incident_service
+    }
+
+    /// `None` when SMTP is not configured.
+    pub fn mailer(&self) -> Option<&Mailer> {
+        self.mailer.as_ref()
+    }
+
     pub fn bot(&self) -> &Bot {
         &self.bot
     }
@@ -54,28 +137,692 @@ impl ServerFatherBot {
         &self.config
     }
 
-    pub async fn check_server_status(&self, server: &ServerModel) -> Result<bool> {
-        monitor::check_server(&server.host, server.port as u16, Duration::from_secs(5)).await
+    /// Role of a user writing in a chat, `None` when they may not use the bot.
+    /// `ADMIN_IDS` are always admins, then a granted role applies, and anyone
+    /// else on the allowlist (or everyone without one) is a viewer. Changing
+    /// servers takes a role only `/grant` or `ADMIN_IDS` hand out.
+    pub async fn role_of(&self, user_id: Option<UserId>, chat_id: ChatId) -> Result<Option<Role>> {
+        let user_id = user_id.map(|user_id| user_id.0 as i64);
+
+        if user_id.is_some_and(|user_id| self.config.admin_ids.contains(&user_id)) {
+            return Ok(Some(Role::Admin));
+        }
+
+        if let Some(user_id) = user_id {
+            if let Some(role) = self.role_service.get_role(user_id).await? {
+                return Ok(Some(role));
+            }
+        }
+
+        let allowed = &self.config.allowed_ids;
+        let listed = allowed.is_empty()
+            || allowed.contains(&chat_id.0)
+            || user_id.is_some_and(|user_id| allowed.contains(&user_id));
+        Ok(listed.then_some(Role::Viewer))
+    }
+
+    /// Saves changes to a server. An incident of the old target is closed when
+    /// the server now points somewhere else, and the chat is told so.
+    pub async fn update_server(
+        &self,
+        chat_id: ChatId,
+        server_id: i32,
+        changes: NewServer,
+    ) -> Result<Option<ServerModel>> {
+        let Some(current) = self.server_service.get_server(chat_id.0, server_id).await? else {
+            return Ok(None);
+        };
+        let Some(server) = self
+            .server_service
+            .update_server(chat_id.0, server_id, changes)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let retargeted = (&current.host, current.port, current.check_kind)
+            != (&server.host, server.port, server.check_kind);
+        if retargeted {
+            if let Some(incident) = self.incident_service.resolve(server.id).await? {
+                let message = format!(
+                    "ℹ️ Incident #{} of '{}' closed, the server was changed to {}.",
+                    incident.id,
+                    server.name,
+                    server.target()
+                );
+                if let Err(e) = self.bot.send_message(chat_id, message).await {
+                    tracing::warn!("Failed to announce closed incident in {}: {}", chat_id, e);
+                }
+            }
+        }
+
+        Ok(Some(server))
+    }
+
+    /// Interval for servers of a chat that have none of their own: the chat's
+    /// setting, then the global one, then `CHECK_INTERVAL`.
+    pub async fn default_check_interval(&self, chat_id: ChatId) -> Result<Duration> {
+        let secs = match self.settings_service.get_interval(Some(chat_id.0)).await? {
+            Some(secs) => Some(secs),
+            None => self.settings_service.get_interval(None).await?,
+        };
+
+        Ok(secs
+            .map(|secs| Duration::from_secs(secs as u64))
+            .unwrap_or(Duration::from_secs(self.config.check_interval)))
+    }
+
+    /// Default interval of a chat, or the longest interval a running monitor
+    /// of the chat uses instead, to tell how often its servers were checked.
+    pub async fn longest_check_interval(&self, chat_id: ChatId) -> Result<Duration> {
+        let default = self.default_check_interval(chat_id).await?;
+        Ok(self
+            .list_monitors(chat_id)
+            .await
+            .iter()
+            .filter_map(|monitor| monitor.control.interval)
+            .fold(default, Duration::max))
+    }
+
+    /// How often a server is checked, falling back to `default` without its own interval.
+    pub fn check_interval(&self, server: &ServerModel, default: Duration) -> Duration {
+        server
+            .check_interval
+            .map(|secs| Duration::from_secs(secs as u64))
+            .unwrap_or(default)
+    }
+
+    /// State change and flapping thresholds of a server, its own or the configured ones.
+    pub fn thresholds(&self, server: &ServerModel) -> Thresholds {
+        Thresholds {
+            failures: server
+                .failure_threshold
+                .map(|failures| failures as u32)
+                .unwrap_or(self.config.failure_threshold),
+            recoveries: server
+                .recovery_threshold
+                .map(|recoveries| recoveries as u32)
+                .unwrap_or(self.config.recovery_threshold),
+            flap_changes: self.config.flap_threshold,
+            flap_window: Duration::from_secs(self.config.flap_window),
+        }
     }
 
+    pub async fn check_server_status(&self, server: &ServerModel) -> Result<CheckOutcome> {
+        let timeout = server
+            .check_timeout
+            .map(|secs| secs as u64)
+            .unwrap_or(self.config.check_timeout);
+        let retries = server
+            .check_retries
+            .map(|retries| retries as u32)
+            .unwrap_or(self.config.check_retries);
+
+        // Retry right away, only the final attempt ends up in the history
+        let mut outcome = monitor::probe(server, Duration::from_secs(timeout)).await?;
+        for _ in 0..retries {
+            if outcome.is_up {
+                break;
+            }
+            outcome = monitor::probe(server, Duration::from_secs(timeout)).await?;
+        }
+
+        if let Err(e) = self.check_service.record_result(server.id, &outcome).await {
+            tracing::warn!("Failed to record check result of '{}': {}", server.name, e);
+        }
+
+        if let Some(certificate) = &outcome.certificate {
+            if let Err(e) = self
+                .server_service
+                .record_certificate(server.id, certificate)
+                .await
+            {
+                tracing::warn!("Failed to record certificate of '{}': {}", server.name, e);
+            }
+        }
+
+        Ok(outcome)
+    }
+
+    /// Probes servers concurrently, at most `max_concurrent_checks` at a time.
+    /// Results keep the order of `servers`; a probe that fails or is still
+    /// running when `check_deadline` passes yields `None`.
+    pub async fn check_servers(&self, servers: &[ServerModel]) -> Vec<Option<CheckOutcome>> {
+        let deadline = Instant::now() + Duration::from_secs(self.config.check_deadline);
+
+        // Collected up front so the spawned monitor future stays `Send`
+        let checks = servers
+            .iter()
+            .map(|server| self.check_server_before(server, deadline))
+            .collect::<Vec<_>>();
+
+        stream::iter(checks)
+            .buffered(self.config.max_concurrent_checks)
+            .collect()
+            .await
+    }
+
+    async fn check_server_before(
+        &self,
+        server: &ServerModel,
+        deadline: Instant,
+    ) -> Option<CheckOutcome> {
+        match timeout_at(deadline, self.check_server_status(server)).await {
+            Ok(Ok(outcome)) => Some(outcome),
+            Ok(Err(e)) => {
+                tracing::warn!("Failed to check '{}': {}", server.name, e);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    /// Announces a state change along with the incident it opened or resolved.
     pub async fn notify_status_change(
         &self,
         server: &ServerModel,
         is_up: bool,
+        incident: Option<&IncidentModel>,
+        failed: &[&AddressOutcome],
+        chat_id: ChatId,
+    ) -> Result<()> {
+        let failed = match failed {
+            [] => String::new(),
+            failed => format!(
+                "\nFailing addresses: {}",
+                failed
+                    .iter()
+                    .map(|address| address.describe())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
+        match (is_up, incident) {
+            (true, Some(incident)) => {
+                let message = format!(
+                    "✅ Server '{}' is back online!\nIncident #{} resolved after {}.",
+                    server.name,
+                    incident.id,
+                    format_span(incident.duration(Utc::now().naive_utc()))
+                );
+                self.bot.send_message(chat_id, message).await?;
+            }
+            (true, None) => {
+                let message = format!("✅ Server '{}' is back online!", server.name);
+                self.bot.send_message(chat_id, message).await?;
+            }
+            (false, Some(incident)) => {
+                let message = format!(
+                    "🚨 Server '{}' is down!{}\nIncident #{} opened, /ack {} to silence reminders.",
+                    server.name, failed, incident.id, incident.id
+                );
+                self.bot
+                    .send_message(chat_id, message)
+                    .reply_markup(ack_keyboard(incident))
+                    .await?;
+            }
+            (false, None) => {
+                let message = format!("🚨 Server '{}' is down!{}", server.name, failed);
+                self.bot.send_message(chat_id, message).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Notification channels set up for a group.
+    pub fn notifiers(&self, group: &ServerGroupModel) -> Vec<Box<dyn Notifier>> {
+        let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+
+        if let Some(url) = &group.webhook_url {
+            match WebhookNotifier::new(
+                url.clone(),
+                group.webhook_secret.clone(),
+                self.config.webhook_retries,
+                Duration::from_secs(self.config.webhook_timeout),
+            ) {
+                Ok(webhook) => notifiers.push(Box::new(webhook)),
+                Err(e) => tracing::warn!("Failed to set up webhook of '{}': {}", group.name, e),
+            }
+        }
+
+        let recipients = group.email_recipients();
+        if let (Some(mailer), false) = (&self.mailer, recipients.is_empty()) {
+            notifiers.push(Box::new(EmailNotifier::new(mailer.clone(), recipients)));
+        }
+
+        notifiers
+    }
+
+    /// Hands an event to the notifiers of every group the server is in, in the
+    /// background so retries do not hold up the monitor.
+    pub fn dispatch_event(&self, server: &ServerModel, event: StatusEvent) {
+        let Some(owner_chat_id) = server.owner_chat_id else {
+            return;
+        };
+        let server_id = server.id;
+        let bot = self.clone();
+
+        tokio::spawn(async move {
+            let groups = match bot
+                .group_service
+                .list_groups_of_server(owner_chat_id, server_id)
+                .await
+            {
+                Ok(groups) => groups,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to load groups of server {} for notifications: {}",
+                        server_id,
+                        e
+                    );
+                    return;
+                }
+            };
+
+            for group in groups {
+                let event = event.clone().with_group(&group);
+                for notifier in bot.notifiers(&group) {
+                    if let Err(e) = notifier.notify(&event).await {
+                        tracing::warn!(
+                            "Failed to notify group '{}' about '{}': {}",
+                            group.name,
+                            event.server.name,
+                            e
+                        );
+                    }
+                }
+            }
+        });
+    }
+
+    /// Reminds of a server that is still down every `realert_interval` until
+    /// its incident is acknowledged.
+    pub async fn remind_incident(&self, server: &ServerModel, chat_id: ChatId) -> Result<()> {
+        if self.config.realert_interval == 0 {
+            return Ok(());
+        }
+        let Some(incident) = self.incident_service.get_open(server.id).await? else {
+            return Ok(());
+        };
+
+        let now = Utc::now().naive_utc();
+        let since_alert = (now - incident.last_alerted_at)
+            .to_std()
+            .unwrap_or_default();
+        if incident.acknowledged_at.is_some()
+            || since_alert < Duration::from_secs(self.config.realert_interval)
+        {
+            return Ok(());
+        }
+
+        let message = format!(
+            "🚨 Server '{}' is still down after {} (incident #{}).",
+            server.name,
+            format_span(incident.duration(now)),
+            incident.id
+        );
+        self.bot
+            .send_message(chat_id, message)
+            .reply_markup(ack_keyboard(&incident))
+            .await?;
+        self.incident_service.set_alerted(incident.id).await?;
+        Ok(())
+    }
+
+    /// Announces that a server started flapping, or settled again after it did.
+    pub async fn notify_flapping(
+        &self,
+        server: &ServerModel,
+        flapping: bool,
+        is_up: bool,
         chat_id: ChatId,
     ) -> Result<()> {
-        let message = if is_up {
-            format!("✅ Server '{}' is back online!", server.name)
+        let state = if is_up { "online" } else { "offline" };
+        let message = if flapping {
+            format!(
+                "〰️ Server '{}' is flapping, now {}. Alerts are paused until it settles.",
+                server.name, state
+            )
         } else {
-            format!("🚨 Server '{}' is down!", server.name)
+            format!(
+                "〰️ Server '{}' stopped flapping and is {}.",
+                server.name, state
+            )
         };
 
         self.bot.send_message(chat_id, message).await?;
         Ok(())
     }
 
-    pub async fn start_monitoring(&self, chat_id: ChatId) -> Result<()> {
-        tokio::spawn(tasks::monitor_servers(Arc::new(self.clone()), chat_id.0));
+    pub async fn notify_cert_expiry(
+        &self,
+        server: &ServerModel,
+        certificate: &CertificateInfo,
+        days_left: i64,
+        chat_id: ChatId,
+    ) -> Result<()> {
+        let expiry = certificate.not_after.format("%Y-%m-%d %H:%M UTC");
+        let message = if days_left < 0 {
+            format!(
+                "🔐 Certificate of '{}' ({}) expired on {}!",
+                server.name, certificate.sni, expiry
+            )
+        } else {
+            format!(
+                "⚠️ Certificate of '{}' ({}) expires in {} day(s) on {}.\nIssuer: {}",
+                server.name, certificate.sni, days_left, expiry, certificate.issuer
+            )
+        };
+
+        self.bot.send_message(chat_id, message).await?;
         Ok(())
     }
+
+    /// Starts a monitor for the scope unless one is already running, in which
+    /// case it is resumed and takes the new interval. The subscription is
+    /// persisted so the monitor comes back after a restart.
+    pub async fn start_monitoring(
+        &self,
+        chat_id: ChatId,
+        scope: MonitorScope,
+        interval: Option<Duration>,
+    ) -> Result<MonitorStart> {
+        let mut monitors = self.monitors.lock().await;
+
+        let running = monitors
+            .iter()
+            .filter(|((chat, _), handle)| *chat == chat_id && !handle.task.is_finished())
+            .map(|((_, monitor_scope), _)| *monitor_scope)
+            .collect::<Vec<_>>();
+        for monitor_scope in running {
+            if self.overlaps(chat_id, scope, monitor_scope).await? {
+                return Ok(MonitorStart::Overlaps(monitor_scope));
+            }
+        }
+
+        self.subscription_service
+            .subscribe(
+                chat_id.0,
+                scope,
+                interval.map(|interval| interval.as_secs() as i32),
+            )
+            .await?;
+
+        if let Some(handle) = monitors.get(&(chat_id, scope)) {
+            if !handle.task.is_finished() {
+                let mut was_paused = false;
+                handle.control.send_modify(|control| {
+                    was_paused = control.paused;
+                    control.paused = false;
+                    control.interval = interval;
+                });
+                return Ok(if was_paused {
+                    MonitorStart::Resumed
+                } else {
+                    MonitorStart::AlreadyRunning
+                });
+            }
+        }
+
+        let control = MonitorControl {
+            paused: false,
+            interval,
+        };
+        self.spawn_monitor(&mut monitors, chat_id, scope, control);
+
+        Ok(MonitorStart::Started)
+    }
+
+    /// Respawns every persisted monitor, meant to run once on startup.
+    pub async fn resume_monitoring(&self) -> Result<()> {
+        let subscriptions = self.subscription_service.list_subscriptions().await?;
+        let mut monitors = self.monitors.lock().await;
+
+        for subscription in &subscriptions {
+            let chat_id = ChatId(subscription.chat_id);
+            let scope = subscription
+                .group_id
+                .map_or(MonitorScope::All, MonitorScope::Group);
+
+            // Subscriptions from before overlaps were rejected, all servers wins
+            if scope != MonitorScope::All
+                && subscriptions
+                    .iter()
+                    .any(|other| other.chat_id == subscription.chat_id && other.group_id.is_none())
+            {
+                tracing::warn!(
+                    "Skipping monitor of group {} in {}, all servers are monitored already",
+                    subscription.group_id.unwrap_or_default(),
+                    chat_id
+                );
+                continue;
+            }
+            let control = MonitorControl {
+                paused: subscription.paused,
+                interval: subscription
+                    .check_interval
+                    .map(|secs| Duration::from_secs(secs as u64)),
+            };
+            self.spawn_monitor(&mut monitors, chat_id, scope, control);
+
+            if !control.paused {
+                let message = format!(
+                    "🔄 Monitoring resumed for {} after a restart.",
+                    self.describe_scope(chat_id, scope).await
+                );
+                if let Err(e) = self.bot.send_message(chat_id, message).await {
+                    tracing::warn!(
+                        "Failed to announce resumed monitoring in {}: {}",
+                        chat_id,
+                        e
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn spawn_monitor(
+        &self,
+        monitors: &mut HashMap<(ChatId, MonitorScope), MonitorHandle>,
+        chat_id: ChatId,
+        scope: MonitorScope,
+        control: MonitorControl,
+    ) {
+        let (control, control_rx) = watch::channel(control);
+        let task = tokio::spawn(tasks::monitor_servers(
+            Arc::new(self.clone()),
+            chat_id.0,
+            scope,
+            control_rx,
+        ));
+        monitors.insert(
+            (chat_id, scope),
+            MonitorHandle {
+                task,
+                control,
+                started_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Stops the chat's monitor for `scope`, or all of them for `None`.
+    /// Returns how many monitors were stopped.
+    pub async fn stop_monitoring(
+        &self,
+        chat_id: ChatId,
+        scope: Option<MonitorScope>,
+    ) -> Result<usize> {
+        self.subscription_service
+            .unsubscribe(chat_id.0, scope)
+            .await?;
+
+        let mut monitors = self.monitors.lock().await;
+        let keys = monitors
+            .keys()
+            .filter(|(chat, monitor_scope)| {
+                *chat == chat_id && scope.is_none_or(|scope| scope == *monitor_scope)
+            })
+            .copied()
+            .collect::<Vec<_>>();
+
+        for key in &keys {
+            if let Some(handle) = monitors.remove(key) {
+                handle.task.abort();
+            }
+        }
+
+        Ok(keys.len())
+    }
+
+    /// Pauses the chat's monitor for `scope`, or all of them for `None`.
+    /// Returns how many running monitors were paused.
+    pub async fn pause_monitoring(
+        &self,
+        chat_id: ChatId,
+        scope: Option<MonitorScope>,
+    ) -> Result<usize> {
+        self.subscription_service
+            .set_paused(chat_id.0, scope, true)
+            .await?;
+
+        let monitors = self.monitors.lock().await;
+        let mut paused = 0;
+
+        for ((chat, monitor_scope), handle) in monitors.iter() {
+            if *chat != chat_id || scope.is_some_and(|scope| scope != *monitor_scope) {
+                continue;
+            }
+            handle.control.send_if_modified(|control| {
+                let was_paused = control.paused;
+                control.paused = true;
+                if !was_paused {
+                    paused += 1;
+                }
+                !was_paused
+            });
+        }
+
+        Ok(paused)
+    }
+
+    /// Whether two different scopes of a chat would check the same servers.
+    async fn overlaps(
+        &self,
+        chat_id: ChatId,
+        scope: MonitorScope,
+        other: MonitorScope,
+    ) -> Result<bool> {
+        match (scope, other) {
+            _ if scope == other => Ok(false),
+            (MonitorScope::Group(group_id), MonitorScope::Group(other_id)) => {
+                let members = self
+                    .server_service
+                    .list_servers_by_group(chat_id.0, group_id)
+                    .await?;
+                let other_members = self
+                    .server_service
+                    .list_servers_by_group(chat_id.0, other_id)
+                    .await?;
+                Ok(members
+                    .iter()
+                    .any(|server| other_members.iter().any(|other| other.id == server.id)))
+            }
+            _ => Ok(true),
+        }
+    }
+
+    /// Leaves out servers a running monitor of a group with a lower ID checks
+    /// already, for servers that joined a second monitored group later on.
+    pub async fn drop_shared_servers(
+        &self,
+        chat_id: ChatId,
+        group_id: i32,
+        mut servers: Vec<ServerModel>,
+    ) -> Vec<ServerModel> {
+        let other_groups = self
+            .list_monitors(chat_id)
+            .await
+            .into_iter()
+            .filter(|monitor| !monitor.control.paused)
+            .filter_map(|monitor| match monitor.scope {
+                MonitorScope::Group(other_id) if other_id < group_id => Some(other_id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for other_id in other_groups {
+            match self
+                .server_service
+                .list_servers_by_group(chat_id.0, other_id)
+                .await
+            {
+                Ok(shared) => servers.retain(|server| shared.iter().all(|s| s.id != server.id)),
+                Err(e) => tracing::warn!("Failed to list servers of group {}: {}", other_id, e),
+            }
+        }
+        servers
+    }
+
+    pub async fn list_monitors(&self, chat_id: ChatId) -> Vec<MonitorInfo> {
+        let monitors = self.monitors.lock().await;
+
+        monitors
+            .iter()
+            .filter(|((chat, _), handle)| *chat == chat_id && !handle.task.is_finished())
+            .map(|((_, scope), handle)| MonitorInfo {
+                scope: *scope,
+                control: *handle.control.borrow(),
+                started_at: handle.started_at,
+            })
+            .collect()
+    }
+
+    /// Human readable name of a chat's monitor scope, e.g. `group 'prod'`.
+    pub async fn describe_scope(&self, chat_id: ChatId, scope: MonitorScope) -> String {
+        match scope {
+            MonitorScope::All => "all servers".to_string(),
+            MonitorScope::Group(group_id) => {
+                match self.group_service.get_group(chat_id.0, group_id).await {
+                    Ok(Some(group)) => format!("group '{}'", group.name),
+                    _ => format!("group {}", group_id),
+                }
+            }
+        }
+    }
+}
+
+/// Button that acknowledges an incident, answered in `commands`.
+pub fn ack_keyboard(incident: &IncidentModel) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new([[InlineKeyboardButton::callback(
+        "✅ Acknowledge",
+        format!("ack:{}", incident.id),
+    )]])
+}
+
+/// Formats a span with its two largest units, e.g. `2d 3h` or `5m 10s`.
+pub fn format_span(span: Duration) -> String {
+    let secs = span.as_secs();
+    let units = [
+        (secs / 86_400, "d"),
+        (secs / 3_600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ];
+
+    let parts = units
+        .iter()
+        .skip_while(|(value, _)| *value == 0)
+        .take(2)
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
 }
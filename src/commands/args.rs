@@ -0,0 +1,227 @@
+/// Arguments of a one-line command like `/addserver "web 1" 10.0.0.5 443 type=https`.
+///
+/// Words are split on whitespace, a word starting with a quote runs to the
+/// matching quote and may contain spaces. Words like `key=value` are options,
+/// their value may be quoted as well: `description="EU servers"`.
+#[derive(Clone, Debug, Default)]
+pub struct Args {
+    words: Vec<Word>,
+}
+
+#[derive(Clone, Debug)]
+struct Word {
+    text: String,
+    /// Position of the `=` when the word is an option.
+    split: Option<usize>,
+}
+
+impl Args {
+    /// Parses the text of a command message, the command itself is skipped.
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        let mut words = Vec::new();
+        let mut chars = text.trim_start().chars().peekable();
+
+        // Skip the command, e.g. /addserver@ServerFatherBot
+        while chars.next_if(|c| !c.is_whitespace()).is_some() {}
+
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if chars.peek().is_none() {
+                break;
+            }
+
+            let mut word = Word {
+                text: String::new(),
+                split: None,
+            };
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                // Quotes only count at the start of a word or value, so "Bob's" stays as it is
+                let at_value = word.text.is_empty() || word.split == Some(word.text.len() - 1);
+                if let Some(close) = closing_quote(c).filter(|_| at_value) {
+                    read_quoted(&mut chars, close, &mut word.text)?;
+                    continue;
+                }
+
+                if c == '=' && word.split.is_none() && is_key(&word.text) {
+                    word.split = Some(word.text.len());
+                }
+                word.text.push(c);
+            }
+            words.push(word);
+        }
+
+        Ok(Self { words })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Every word as written, options included, for commands that take free text.
+    pub fn words(&self) -> Vec<&str> {
+        self.words.iter().map(|word| word.text.as_str()).collect()
+    }
+
+    /// Words that are not options.
+    pub fn values(&self) -> Vec<&str> {
+        self.words
+            .iter()
+            .filter(|word| word.split.is_none())
+            .map(|word| word.text.as_str())
+            .collect()
+    }
+
+    /// Options as key and value, keys are lowercased.
+    pub fn options(&self) -> Vec<(String, &str)> {
+        self.words
+            .iter()
+            .filter_map(|word| {
+                let split = word.split?;
+                Some((
+                    word.text[..split].to_ascii_lowercase(),
+                    &word.text[split + 1..],
+                ))
+            })
+            .collect()
+    }
+
+    /// Value of an option, the key is matched case-insensitively.
+    pub fn option(&self, key: &str) -> Option<&str> {
+        self.options()
+            .into_iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    /// Fails on the first option not in `known`.
+    pub fn expect_options(&self, known: &[&str]) -> std::result::Result<(), String> {
+        match self
+            .options()
+            .into_iter()
+            .find(|(key, _)| !known.contains(&key.as_str()))
+        {
+            Some((key, _)) => Err(format!("Unknown option '{}'", key)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Quote closing the one that opened with `c`, also the typographic ones
+/// phone keyboards put in.
+fn closing_quote(c: char) -> Option<char> {
+    match c {
+        '"' => Some('"'),
+        '\'' => Some('\''),
+        '“' | '„' => Some('”'),
+        '‘' => Some('’'),
+        '«' => Some('»'),
+        _ => None,
+    }
+}
+
+/// Reads up to the closing quote, `\` escapes the next character.
+fn read_quoted(
+    chars: &mut impl Iterator<Item = char>,
+    close: char,
+    text: &mut String,
+) -> std::result::Result<(), String> {
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => text.extend(chars.next()),
+            c if c == close => return Ok(()),
+            c => text.push(c),
+        }
+    }
+    Err(format!("Missing closing {} quote", close))
+}
+
+fn is_key(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Args {
+        Args::parse(text).unwrap()
+    }
+
+    #[test]
+    fn skips_the_command() {
+        let args = parse("/addserver@ServerFatherBot web 10.0.0.5");
+        assert_eq!(args.values(), ["web", "10.0.0.5"]);
+        assert!(parse("  /status  ").is_empty());
+    }
+
+    #[test]
+    fn quoted_value_keeps_spaces() {
+        let args = parse("/addserver \"web 1\" 10.0.0.5 'port  443'");
+        assert_eq!(args.values(), ["web 1", "10.0.0.5", "port  443"]);
+    }
+
+    #[test]
+    fn quoted_option_value() {
+        let args = parse("/creategroup eu description=\"EU servers\" type=http");
+        assert_eq!(args.values(), ["eu"]);
+        assert_eq!(args.option("description"), Some("EU servers"));
+        assert_eq!(args.option("TYPE"), Some("http"));
+        assert_eq!(args.words(), ["eu", "description=EU servers", "type=http"]);
+    }
+
+    #[test]
+    fn apostrophe_inside_a_word_is_kept() {
+        let args = parse("/renamegroup 3 Bob's servers");
+        assert_eq!(args.values(), ["3", "Bob's", "servers"]);
+    }
+
+    #[test]
+    fn typographic_quotes() {
+        let args = parse("/addserver “web 1” ‘db 2’ «cache 3» „old 4” x=“a b”");
+        assert_eq!(args.values(), ["web 1", "db 2", "cache 3", "old 4"]);
+        assert_eq!(args.option("x"), Some("a b"));
+    }
+
+    #[test]
+    fn backslash_escapes_inside_quotes() {
+        let args = parse(r#"/creategroup "say \"hi\"" 'it\'s' "back\\slash""#);
+        assert_eq!(args.values(), [r#"say "hi""#, "it's", r"back\slash"]);
+    }
+
+    #[test]
+    fn unterminated_quote_is_an_error() {
+        assert_eq!(
+            Args::parse("/addserver \"web 1 10.0.0.5").unwrap_err(),
+            "Missing closing \" quote"
+        );
+        assert!(Args::parse("/creategroup eu description='EU").is_err());
+    }
+
+    #[test]
+    fn only_the_first_equals_sign_splits() {
+        let args = parse("/addserver a=b=c");
+        assert!(args.values().is_empty());
+        assert_eq!(args.options(), [("a".to_string(), "b=c")]);
+    }
+
+    #[test]
+    fn words_that_are_not_keys_stay_values() {
+        let args = parse("/addserver =x https://example.com/?a=b \"k=v\"");
+        assert_eq!(args.values(), ["=x", "https://example.com/?a=b", "k=v"]);
+        assert!(args.options().is_empty());
+    }
+
+    #[test]
+    fn expect_options_rejects_unknown_keys() {
+        let args = parse("/editserver 3 interval=60 colour=red");
+        assert_eq!(
+            args.expect_options(&["interval", "timeout"]).unwrap_err(),
+            "Unknown option 'colour'"
+        );
+        assert!(args.expect_options(&["interval", "colour"]).is_ok());
+        assert!(parse("/editserver 3").expect_options(&[]).is_ok());
+    }
+}
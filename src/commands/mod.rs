@@ -1,18 +1,43 @@
-use crate::bot::ServerFatherBot;
+mod args;
+
+use self::args::Args;
+use crate::bot::{format_span, MonitorStart, ServerFatherBot};
+use crate::db::dialogue_storage::DialogueStorage;
+use crate::db::entities::check_result::ErrorKind;
+use crate::db::entities::server::{CheckKind, Model as ServerModel};
+use crate::db::entities::server_group::Model as ServerGroupModel;
+use crate::db::entities::user_role::Role;
 use crate::error::{BotError, Result};
+use crate::monitor::address::{self, HostInput};
+use crate::monitor::http::HttpCheck;
+use crate::monitor::{CheckOutcome, MonitorScope};
+use crate::notify::email;
+use crate::notify::webhook::WebhookNotifier;
+use crate::notify::{Notifier, StatusEvent};
+use crate::services::check::UptimeReport;
+use crate::services::server::{CheckSettings, NewServer};
+use chrono::{NaiveDate, NaiveDateTime, Utc};
+use sea_orm::ActiveEnum;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use teloxide::{
-    dispatching::{
-        dialogue::{Dialogue, InMemStorage},
-        UpdateHandler,
-    },
+    dispatching::{dialogue::Dialogue, UpdateHandler},
     prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, Me, User},
     utils::command::BotCommands,
 };
 
-type MyDialogue = Dialogue<State, InMemStorage<State>>;
+pub type StateStorage = DialogueStorage<State>;
+type MyDialogue = Dialogue<State, StateStorage>;
+type ParsedArgs = std::result::Result<Args, String>;
+
+/// How often idle dialogues are looked for.
+const DIALOGUE_SWEEP_INTERVAL: Duration = Duration::from_secs(60);
 
-#[derive(Clone, Default)]
+/// Where a chat is in a wizard, kept in the database between updates.
+#[derive(Clone, Default, Serialize, Deserialize)]
 pub enum State {
     #[default]
     Start,
@@ -24,12 +49,81 @@ pub enum State {
     AwaitingServerPort {
         name: String,
     },
+    AwaitingCheckKind {
+        host: String,
+        port: i32,
+        name: String,
+    },
+    AwaitingHttpOptions {
+        host: String,
+        port: i32,
+        name: String,
+        kind: CheckKind,
+    },
+    AwaitingCheckSettings {
+        server: NewServer,
+    },
     AwaitingServerId,
     AwaitingGroupName,
-    AwaitingGroupId,
+    AwaitingGroupIdForServer,
+    AwaitingGroupIdForRemoval,
+    AwaitingGroupIdForUnassign,
+    AwaitingServerToUnassign {
+        group_id: i32,
+    },
     AwaitingServerForGroup {
         group_id: i32,
     },
+    EditingServer {
+        server_id: i32,
+        field: EditField,
+        draft: NewServer,
+    },
+}
+
+/// Fields `/editserver` walks through, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EditField {
+    Name,
+    Host,
+    Port,
+    CheckKind,
+    /// Only asked for HTTP(S) checks
+    HttpOptions,
+    Settings,
+}
+
+impl State {
+    /// Least role allowed to answer the prompt, every wizard changes something.
+    fn required_role(&self) -> Role {
+        match self {
+            State::Start => Role::Viewer,
+            _ => Role::Operator,
+        }
+    }
+
+    /// What the chat is in the middle of, for messages about leaving it.
+    fn activity(&self) -> &'static str {
+        match self {
+            State::Start => "nothing",
+            State::AwaitingServerHost
+            | State::AwaitingServerPort { .. }
+            | State::AwaitingServerName { .. }
+            | State::AwaitingCheckKind { .. }
+            | State::AwaitingHttpOptions { .. }
+            | State::AwaitingCheckSettings { .. } => "adding a server",
+            State::AwaitingServerId => "removing a server",
+            State::AwaitingGroupName => "creating a group",
+            State::AwaitingGroupIdForServer | State::AwaitingServerForGroup { .. } => {
+                "adding a server to a group"
+            }
+            State::AwaitingGroupIdForRemoval => "removing a group",
+            State::AwaitingGroupIdForUnassign | State::AwaitingServerToUnassign { .. } => {
+                "removing a server from a group"
+            }
+            State::EditingServer { .. } => "editing a server",
+        }
+    }
 }
 
 #[derive(BotCommands, Clone)]
@@ -37,6 +131,8 @@ pub enum State {
 pub enum Command {
     #[command(description = "Start the bot")]
     Start,
+    #[command(description = "Leave the current step")]
+    Cancel,
     #[command(description = "Add a new server")]
     AddServer,
     #[command(description = "Remove a server")]
@@ -51,664 +147,3563 @@ pub enum Command {
     Status,
     #[command(description = "Start monitoring servers")]
     Monitor,
+    #[command(description = "Stop monitoring servers")]
+    StopMonitor,
+    #[command(description = "Pause monitoring servers")]
+    PauseMonitor,
+    #[command(description = "List running monitors")]
+    Monitors,
     #[command(description = "List all groups")]
     Groups,
     #[command(description = "Add server to group")]
     AddToGroup,
+    #[command(description = "Show a group and its servers")]
+    Group,
+    #[command(description = "Rename a group")]
+    RenameGroup,
+    #[command(description = "Set the description of a group")]
+    DescribeGroup,
+    #[command(description = "Remove a server from a group")]
+    RemoveFromGroup,
     #[command(description = "Remove a group")]
     RemoveGroup,
     #[command(description = "Check group status")]
     CheckGroup,
+    #[command(description = "Show uptime of a server or group")]
+    Uptime,
+    #[command(description = "Change check settings of a server")]
+    EditServer,
+    #[command(description = "Set the webhook of a group")]
+    SetWebhook,
+    #[command(description = "Set the mail recipients of a group")]
+    SetEmail,
+    #[command(description = "Acknowledge an incident")]
+    Ack,
+    #[command(description = "Grant a role to a user")]
+    Grant,
+    #[command(description = "Revoke the role of a user")]
+    Revoke,
+}
+
+impl Command {
+    /// Least role allowed to run the command.
+    fn required_role(&self) -> Role {
+        match self {
+            Command::Start
+            | Command::Cancel
+            | Command::Status
+            | Command::Check
+            | Command::Monitors
+            | Command::Groups
+            | Command::Group
+            | Command::CheckGroup
+            | Command::Uptime => Role::Viewer,
+            Command::AddServer
+            | Command::RemoveServer
+            | Command::CreateGroup
+            | Command::SetInterval
+            | Command::Monitor
+            | Command::StopMonitor
+            | Command::PauseMonitor
+            | Command::AddToGroup
+            | Command::RemoveFromGroup
+            | Command::RenameGroup
+            | Command::DescribeGroup
+            | Command::RemoveGroup
+            | Command::EditServer
+            | Command::Ack => Role::Operator,
+            Command::SetWebhook | Command::SetEmail | Command::Grant | Command::Revoke => {
+                Role::Admin
+            }
+        }
+    }
+}
+
+/// Goes in front of `schema()`: ignores users outside the allowlist, refuses
+/// commands and prompts above the sender's role and hands the `Role` on.
+pub fn authorize() -> UpdateHandler<BotError> {
+    dptree::filter_map_async(authorize_message)
+}
+
+async fn authorize_message(
+    bot: Bot,
+    me: Me,
+    msg: Message,
+    state: State,
+    server_father: Arc<ServerFatherBot>,
+) -> Option<Role> {
+    let user_id = msg.from.as_ref().map(|user| user.id);
+    let role = match server_father.role_of(user_id, msg.chat.id).await {
+        Ok(Some(role)) => role,
+        Ok(None) => {
+            tracing::info!(
+                "Ignoring message from chat {} outside the allowlist",
+                msg.chat.id
+            );
+            return None;
+        }
+        Err(e) => {
+            tracing::warn!("Failed to look up role in chat {}: {}", msg.chat.id, e);
+            return None;
+        }
+    };
+
+    let required = match msg
+        .text()
+        .and_then(|text| Command::parse(text, me.username()).ok())
+    {
+        Some(command) => command.required_role(),
+        None => state.required_role(),
+    };
+
+    if role < required {
+        let message = format!(
+            "⛔ This needs the {} role, you are {}. An admin can /grant it.",
+            required, role
+        );
+        if let Err(e) = bot.send_message(msg.chat.id, message).await {
+            tracing::warn!("Failed to refuse message in chat {}: {}", msg.chat.id, e);
+        }
+        return None;
+    }
+
+    Some(role)
 }
 
 pub fn schema() -> UpdateHandler<BotError> {
     use dptree::case;
 
-    let command_handler = teloxide::filter_command::<Command, _>().branch(
-        case![State::Start]
-            .branch(case![Command::Start].endpoint(start))
-            .branch(case![Command::AddServer].endpoint(add_server))
-            .branch(case![Command::Status].endpoint(status))
-            .branch(case![Command::Monitor].endpoint(start_monitoring))
-            .branch(case![Command::RemoveServer].endpoint(remove_server))
-            .branch(case![Command::Check].endpoint(check_server))
-            .branch(case![Command::CreateGroup].endpoint(create_group))
-            .branch(case![Command::Groups].endpoint(list_groups))
-            .branch(case![Command::AddToGroup].endpoint(add_to_group))
-            .branch(case![Command::RemoveGroup].endpoint(remove_group))
-            .branch(case![Command::CheckGroup].endpoint(check_group)),
-    );
+    // Any other command leaves an unfinished wizard first
+    let command_handler = teloxide::filter_command::<Command, _>()
+        .branch(case![Command::Cancel].endpoint(cancel))
+        .map(|msg: Message| Args::parse(msg.text().unwrap_or_default()))
+        .branch(dptree::filter_map(|args: ParsedArgs| args.err()).endpoint(invalid_args))
+        .branch(
+            dptree::filter_map(|args: ParsedArgs| args.ok())
+                .map_async(leave_wizard)
+                .branch(case![Command::Start].endpoint(start))
+                .branch(case![Command::AddServer].endpoint(add_server))
+                .branch(case![Command::Status].endpoint(status))
+                .branch(case![Command::Monitor].endpoint(start_monitoring))
+                .branch(case![Command::StopMonitor].endpoint(stop_monitoring))
+                .branch(case![Command::PauseMonitor].endpoint(pause_monitoring))
+                .branch(case![Command::Monitors].endpoint(list_monitors))
+                .branch(case![Command::RemoveServer].endpoint(remove_server))
+                .branch(case![Command::Check].endpoint(check_server))
+                .branch(case![Command::CreateGroup].endpoint(create_group))
+                .branch(case![Command::Groups].endpoint(list_groups))
+                .branch(case![Command::Group].endpoint(show_group))
+                .branch(case![Command::RenameGroup].endpoint(rename_group))
+                .branch(case![Command::DescribeGroup].endpoint(describe_group))
+                .branch(case![Command::AddToGroup].endpoint(add_to_group))
+                .branch(case![Command::RemoveFromGroup].endpoint(remove_from_group))
+                .branch(case![Command::RemoveGroup].endpoint(remove_group))
+                .branch(case![Command::CheckGroup].endpoint(check_group))
+                .branch(case![Command::Uptime].endpoint(uptime))
+                .branch(case![Command::EditServer].endpoint(edit_server))
+                .branch(case![Command::SetInterval].endpoint(set_interval))
+                .branch(case![Command::SetWebhook].endpoint(set_webhook))
+                .branch(case![Command::SetEmail].endpoint(set_email))
+                .branch(case![Command::Ack].endpoint(ack_incident))
+                .branch(case![Command::Grant].endpoint(grant_role))
+                .branch(case![Command::Revoke].endpoint(revoke_role)),
+        );
 
     let message_handler = Update::filter_message()
         .branch(command_handler)
         .branch(case![State::AwaitingServerHost].endpoint(receive_host))
         .branch(case![State::AwaitingServerPort { name }].endpoint(receive_port))
         .branch(case![State::AwaitingServerName { host, port }].endpoint(receive_name))
+        .branch(case![State::AwaitingCheckKind { host, port, name }].endpoint(receive_check_kind))
+        .branch(
+            case![State::AwaitingHttpOptions {
+                host,
+                port,
+                name,
+                kind
+            }]
+            .endpoint(receive_http_options),
+        )
+        .branch(case![State::AwaitingCheckSettings { server }].endpoint(receive_check_settings))
         .branch(case![State::AwaitingServerId].endpoint(receive_server_id))
         .branch(case![State::AwaitingGroupName].endpoint(receive_group_name))
-        .branch(case![State::AwaitingGroupId].endpoint(receive_group_id_for_server))
+        .branch(case![State::AwaitingGroupIdForServer].endpoint(receive_group_id_for_server))
         .branch(
             case![State::AwaitingServerForGroup { group_id }].endpoint(receive_server_for_group),
         )
-        .branch(case![State::AwaitingGroupId].endpoint(receive_group_id_for_removal));
+        .branch(case![State::AwaitingGroupIdForRemoval].endpoint(receive_group_id_for_removal))
+        .branch(case![State::AwaitingGroupIdForUnassign].endpoint(receive_group_id_for_unassign))
+        .branch(
+            case![State::AwaitingServerToUnassign { group_id }]
+                .endpoint(receive_server_to_unassign),
+        )
+        .branch(
+            case![State::EditingServer {
+                server_id,
+                field,
+                draft
+            }]
+            .endpoint(receive_server_edit),
+        );
 
     message_handler.endpoint(invalid_state)
 }
 
-async fn start(bot: Bot, msg: Message) -> Result<()> {
-    bot.send_message(
-        msg.chat.id,
-        "👋 Welcome to Server Father!\nI'll help you monitor your servers.",
-    )
-    .await?;
-    Ok(())
+/// Answers inline buttons, authorized like the commands behind them. Buttons
+/// share the dialogue with typed answers, so either can finish a prompt.
+pub fn callback_schema() -> UpdateHandler<BotError> {
+    Update::filter_callback_query()
+        .filter_map_async(authorize_callback)
+        .enter_dialogue::<CallbackQuery, StateStorage, State>()
+        .endpoint(handle_callback)
 }
 
-async fn add_server(bot: Bot, dialogue: MyDialogue, msg: Message) -> Result<()> {
-    dialogue.update(State::AwaitingServerHost).await?;
-    bot.send_message(msg.chat.id, "Please enter the server host (IP or domain):")
-        .await?;
-    Ok(())
+/// What an inline button does, carried in its callback data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Ack(i32),
+    RemoveServer(i32),
+    ConfirmRemoveServer(i32),
+    /// Group picked in `/addtogroup`, the server comes next
+    PickGroup(i32),
+    AddToGroup {
+        group_id: i32,
+        server_id: i32,
+    },
+    /// Group picked in `/removefromgroup`, the member comes next
+    PickGroupToLeave(i32),
+    RemoveFromGroup {
+        group_id: i32,
+        server_id: i32,
+    },
+    RemoveGroup(i32),
+    ConfirmRemoveGroup(i32),
+    /// Removes the group's servers along with it
+    ConfirmRemoveGroupWithServers(i32),
+    Cancel,
 }
 
-async fn receive_host(bot: Bot, dialogue: MyDialogue, msg: Message) -> Result<()> {
-    let host = msg.text().unwrap_or_default().to_string();
+impl Action {
+    /// Callback data, kept well below Telegram's 64 bytes.
+    fn data(self) -> String {
+        match self {
+            Action::Ack(id) => format!("ack:{}", id),
+            Action::RemoveServer(id) => format!("rmserver:{}", id),
+            Action::ConfirmRemoveServer(id) => format!("rmserver:{}:yes", id),
+            Action::PickGroup(id) => format!("addgroup:{}", id),
+            Action::AddToGroup {
+                group_id,
+                server_id,
+            } => format!("addgroup:{}:{}", group_id, server_id),
+            Action::PickGroupToLeave(id) => format!("rmfromgroup:{}", id),
+            Action::RemoveFromGroup {
+                group_id,
+                server_id,
+            } => format!("rmfromgroup:{}:{}", group_id, server_id),
+            Action::RemoveGroup(id) => format!("rmgroup:{}", id),
+            Action::ConfirmRemoveGroup(id) => format!("rmgroup:{}:yes", id),
+            Action::ConfirmRemoveGroupWithServers(id) => format!("rmgroup:{}:all", id),
+            Action::Cancel => "cancel".to_string(),
+        }
+    }
 
-    dialogue
-        .update(State::AwaitingServerPort { name: host })
-        .await?;
+    fn parse(data: &str) -> Option<Self> {
+        let parts = data.split(':').collect::<Vec<_>>();
+        let id = |id: &str| id.parse::<i32>().ok();
+
+        match parts.as_slice() {
+            ["ack", incident_id] => id(incident_id).map(Action::Ack),
+            ["rmserver", server_id] => id(server_id).map(Action::RemoveServer),
+            ["rmserver", server_id, "yes"] => id(server_id).map(Action::ConfirmRemoveServer),
+            ["addgroup", group_id] => id(group_id).map(Action::PickGroup),
+            ["addgroup", group_id, server_id] => Some(Action::AddToGroup {
+                group_id: id(group_id)?,
+                server_id: id(server_id)?,
+            }),
+            ["rmfromgroup", group_id] => id(group_id).map(Action::PickGroupToLeave),
+            ["rmfromgroup", group_id, server_id] => Some(Action::RemoveFromGroup {
+                group_id: id(group_id)?,
+                server_id: id(server_id)?,
+            }),
+            ["rmgroup", group_id] => id(group_id).map(Action::RemoveGroup),
+            ["rmgroup", group_id, "yes"] => id(group_id).map(Action::ConfirmRemoveGroup),
+            ["rmgroup", group_id, "all"] => id(group_id).map(Action::ConfirmRemoveGroupWithServers),
+            ["cancel"] => Some(Action::Cancel),
+            _ => None,
+        }
+    }
 
-    bot.send_message(msg.chat.id, "Please enter the port number:")
-        .await?;
+    /// Whether the button belongs to the prompt the chat is waiting on. Buttons
+    /// of older messages or other prompts must not end a running wizard.
+    fn answers(self, state: &State) -> bool {
+        match (self, state) {
+            (Action::RemoveServer(_), State::AwaitingServerId)
+            | (Action::PickGroup(_), State::AwaitingGroupIdForServer)
+            | (Action::PickGroupToLeave(_), State::AwaitingGroupIdForUnassign)
+            | (Action::RemoveGroup(_), State::AwaitingGroupIdForRemoval) => true,
+            (
+                Action::AddToGroup { group_id, .. },
+                State::AwaitingServerForGroup { group_id: g },
+            )
+            | (
+                Action::RemoveFromGroup { group_id, .. },
+                State::AwaitingServerToUnassign { group_id: g },
+            ) => group_id == *g,
+            (
+                Action::Cancel,
+                State::AwaitingServerId
+                | State::AwaitingGroupIdForServer
+                | State::AwaitingGroupIdForUnassign
+                | State::AwaitingGroupIdForRemoval
+                | State::AwaitingServerForGroup { .. }
+                | State::AwaitingServerToUnassign { .. },
+            ) => true,
+            _ => false,
+        }
+    }
 
-    Ok(())
+    fn button(self, text: impl Into<String>) -> InlineKeyboardButton {
+        InlineKeyboardButton::callback(text, self.data())
+    }
 }
 
-async fn receive_port(bot: Bot, dialogue: MyDialogue, msg: Message, state: State) -> Result<()> {
-    let port = msg
-        .text()
-        .and_then(|text| text.parse::<i32>().ok())
-        .unwrap_or(0);
+/// One button per item and row, with a cancel button below.
+fn pick_keyboard<T>(
+    items: &[T],
+    button: impl Fn(&T) -> InlineKeyboardButton,
+) -> InlineKeyboardMarkup {
+    let rows = items
+        .iter()
+        .map(|item| vec![button(item)])
+        .chain(std::iter::once(vec![Action::Cancel.button("✖️ Cancel")]));
+    InlineKeyboardMarkup::new(rows)
+}
 
-    if port <= 0 || port > 65535 {
-        bot.send_message(
-            msg.chat.id,
-            "Invalid port number. Please enter a number between 1 and 65535:",
-        )
-        .await?;
-        return Ok(());
-    }
+fn confirm_keyboard(confirm: Action, label: &str) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new([[confirm.button(label), Action::Cancel.button("✖️ Cancel")]])
+}
 
-    if let State::AwaitingServerPort { name } = state {
-        dialogue
-            .update(State::AwaitingServerName { host: name, port })
-            .await?;
+/// Text of a reply and the buttons that go with it.
+type Reply = (String, Option<InlineKeyboardMarkup>);
 
-        bot.send_message(msg.chat.id, "Please enter a name for this server:")
-            .await?;
+async fn authorize_callback(
+    bot: Bot,
+    q: CallbackQuery,
+    server_father: Arc<ServerFatherBot>,
+) -> Option<ChatId> {
+    let chat_id = q.message.as_ref().map(|message| message.chat().id)?;
+    let role = match server_father.role_of(Some(q.from.id), chat_id).await {
+        Ok(Some(role)) => role,
+        Ok(None) => return None,
+        Err(e) => {
+            tracing::warn!("Failed to look up role in chat {}: {}", chat_id, e);
+            return None;
+        }
+    };
+
+    // Every button so far changes something
+    if role < Role::Operator {
+        let message = format!(
+            "⛔ This needs the {} role, you are {}. An admin can /grant it.",
+            Role::Operator,
+            role
+        );
+        if let Err(e) = bot.answer_callback_query(&q.id).text(message).await {
+            tracing::warn!("Failed to refuse button in chat {}: {}", chat_id, e);
+        }
+        return None;
     }
 
-    Ok(())
+    Some(chat_id)
 }
 
-async fn receive_name(
+async fn handle_callback(
     bot: Bot,
-    dialogue: MyDialogue,
+    q: CallbackQuery,
     server_father: Arc<ServerFatherBot>,
-    msg: Message,
-    state: State,
+    dialogue: MyDialogue,
+    chat_id: ChatId,
 ) -> Result<()> {
-    let name = msg.text().unwrap_or_default().to_string();
+    let Some(action) = q.data.as_deref().and_then(Action::parse) else {
+        bot.answer_callback_query(&q.id).await?;
+        return Ok(());
+    };
 
-    if let State::AwaitingServerName { host, port } = state {
-        match server_father
-            .server_service()
-            .add_server(name.clone(), host.clone(), port, None)
-            .await
-        {
-            Ok(_) => {
-                bot.send_message(
-                    msg.chat.id,
-                    format!("✅ Server '{}' ({}) added successfully!", name, host),
-                )
-                .await?;
-            }
-            Err(e) => {
-                bot.send_message(msg.chat.id, format!("❌ Failed to add server: {}", e))
-                    .await?;
+    if let Action::Ack(incident_id) = action {
+        let (acknowledged, message) =
+            acknowledge_incident(&server_father, chat_id, incident_id, Some(&q.from)).await?;
+        if acknowledged {
+            if let Some(alert) = &q.message {
+                bot.edit_message_reply_markup(chat_id, alert.id()).await?;
             }
+            bot.send_message(chat_id, &message).await?;
         }
-
-        dialogue.update(State::Start).await?;
+        bot.answer_callback_query(&q.id).text(message).await?;
+        return Ok(());
     }
 
-    Ok(())
-}
+    // A button answering the pending prompt moves it on, typed IDs are no longer expected
+    let state = dialogue.get().await?.unwrap_or_default();
+    if action.answers(&state) {
+        let next_state = match action {
+            Action::PickGroup(group_id) => State::AwaitingServerForGroup { group_id },
+            Action::PickGroupToLeave(group_id) => State::AwaitingServerToUnassign { group_id },
+            _ => State::Start,
+        };
+        dialogue.update(next_state).await?;
+    }
 
-async fn status(bot: Bot, server_father: Arc<ServerFatherBot>, msg: Message) -> Result<()> {
-    let servers = match server_father.server_service().list_servers().await {
-        Ok(servers) => servers,
-        Err(e) => {
-            bot.send_message(msg.chat.id, format!("❌ Failed to fetch servers: {}", e))
-                .await?;
-            return Ok(());
+    let (text, keyboard) = match action {
+        Action::RemoveServer(server_id) => {
+            server_removal_prompt(&server_father, chat_id, server_id).await?
         }
+        Action::ConfirmRemoveServer(server_id) => (
+            remove_server_by_id(&server_father, chat_id, server_id).await,
+            None,
+        ),
+        Action::PickGroup(group_id) => server_picker(&server_father, chat_id, group_id).await?,
+        Action::AddToGroup {
+            group_id,
+            server_id,
+        } => (
+            add_server_to_group(&server_father, chat_id, group_id, server_id).await,
+            None,
+        ),
+        Action::PickGroupToLeave(group_id) => {
+            member_picker(&server_father, chat_id, group_id).await?
+        }
+        Action::RemoveFromGroup {
+            group_id,
+            server_id,
+        } => (
+            remove_server_from_group(&server_father, chat_id, group_id, server_id).await,
+            None,
+        ),
+        Action::RemoveGroup(group_id) => {
+            group_removal_prompt(&server_father, chat_id, group_id).await?
+        }
+        Action::ConfirmRemoveGroup(group_id) => (
+            remove_group_by_id(&server_father, chat_id, group_id).await,
+            None,
+        ),
+        Action::ConfirmRemoveGroupWithServers(group_id) => (
+            remove_group_with_servers(&server_father, chat_id, group_id).await,
+            None,
+        ),
+        Action::Cancel | Action::Ack(_) => ("Cancelled.".to_string(), None),
     };
 
-    if servers.is_empty() {
-        bot.send_message(
-            msg.chat.id,
-            "No servers added yet. Use /addserver to add one.",
-        )
-        .await?;
-        return Ok(());
-    }
-
-    let mut status_message = String::from("📊 *Server Status*\n\n");
-    let mut status_checks = Vec::with_capacity(servers.len());
-
-    // First collect all servers and their status
-    for server in servers.iter() {
-        let is_up = server_father
-            .check_server_status(server)
-            .await
-            .unwrap_or(false);
-        status_checks.push((server, is_up));
-    }
-
-    // Then format the message
-    for (server, is_up) in status_checks {
-        let status_emoji = if is_up { "🟢" } else { "🔴" };
-        let escaped_name = server
-            .name
-            .replace(|c: char| "[]()~`>#+-=|{}.!".contains(c), r"\$0");
-        let escaped_host = server
-            .host
-            .replace(|c: char| "[]()~`>#+-=|{}.!".contains(c), r"\$0");
-        status_message.push_str(&format!(
-            "{} *{}* \\(ID: {}\\)\n`{}:{}`\n\n",
-            status_emoji, escaped_name, server.id, escaped_host, server.port
-        ));
+    // The keyboard message turns into the next step, so old buttons cannot be pressed again
+    match &q.message {
+        Some(prompt) => {
+            let edit = bot.edit_message_text(chat_id, prompt.id(), text);
+            match keyboard {
+                Some(keyboard) => edit.reply_markup(keyboard).await?,
+                None => edit.await?,
+            };
+        }
+        None => {
+            let send = bot.send_message(chat_id, text);
+            match keyboard {
+                Some(keyboard) => send.reply_markup(keyboard).await?,
+                None => send.await?,
+            };
+        }
     }
-
-    bot.send_message(msg.chat.id, status_message)
-        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-        .await?;
+    bot.answer_callback_query(&q.id).await?;
 
     Ok(())
 }
 
-async fn invalid_state(bot: Bot, msg: Message) -> Result<()> {
-    bot.send_message(
-        msg.chat.id,
-        "⚠️ Invalid command for current state. Try /start",
-    )
-    .await?;
+async fn send_reply(bot: &Bot, chat_id: ChatId, (text, keyboard): Reply) -> Result<()> {
+    let reply = bot.send_message(chat_id, text);
+    match keyboard {
+        Some(keyboard) => reply.reply_markup(keyboard).await?,
+        None => reply.await?,
+    };
     Ok(())
 }
 
-async fn start_monitoring(
-    bot: Bot,
-    server_father: Arc<ServerFatherBot>,
-    msg: Message,
-) -> Result<()> {
-    match server_father.start_monitoring(msg.chat.id).await {
-        Ok(_) => {
+/// Reads a server or group ID argument, tells the user and returns `None` when
+/// it is not a number.
+async fn parse_id(bot: &Bot, chat_id: ChatId, text: &str, kind: &str) -> Result<Option<i32>> {
+    match text.trim_start_matches('#').parse::<i32>() {
+        Ok(id) => Ok(Some(id)),
+        Err(_) => {
             bot.send_message(
-                msg.chat.id,
-                "✅ Monitoring started! You'll receive notifications when server status changes.",
+                chat_id,
+                format!("Invalid {} ID. Please enter a number.", kind),
             )
             .await?;
-        }
-        Err(e) => {
-            bot.send_message(msg.chat.id, format!("❌ Failed to start monitoring: {}", e))
-                .await?;
+            Ok(None)
         }
     }
-    Ok(())
 }
 
-async fn remove_server(bot: Bot, dialogue: MyDialogue, msg: Message) -> Result<()> {
-    dialogue.update(State::AwaitingServerId).await?;
-
+async fn start(bot: Bot, msg: Message) -> Result<()> {
     bot.send_message(
         msg.chat.id,
-        "Please enter the server ID to remove (use /status to see server IDs):",
+        "👋 Welcome to Server Father!\nI'll help you monitor your servers.",
     )
     .await?;
-
     Ok(())
 }
 
-async fn receive_server_id(
+/// Options `/addserver` takes after name, host and port.
+const ADD_SERVER_OPTIONS: &[&str] = &[
+    "type",
+    "http",
+    "group",
+    "interval",
+    "timeout",
+    "retries",
+    "failures",
+    "recoveries",
+    "family",
+    "addresses",
+];
+
+async fn add_server(
     bot: Bot,
     dialogue: MyDialogue,
     server_father: Arc<ServerFatherBot>,
     msg: Message,
+    args: Args,
 ) -> Result<()> {
-    let server_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
-        Some(id) => id,
-        None => {
-            bot.send_message(msg.chat.id, "Invalid server ID. Please enter a number.")
-                .await?;
+    if args.is_empty() {
+        dialogue.update(State::AwaitingServerHost).await?;
+        bot.send_message(msg.chat.id, "Please enter the server host (IP or domain):")
+            .await?;
+        return Ok(());
+    }
+
+    let (name, host, port) = match args.values()[..] {
+        [name, host] => (name, host, None),
+        [name, host, port] => (name, host, Some(port)),
+        _ => {
+            bot.send_message(
+                msg.chat.id,
+                "Usage: /addserver <name> <host> <port> [type=tcp|http|https|tls] \
+             [http=\"HEAD /health 200-299\"] [group=<group_id|name>] [interval=60 timeout=5 ...]\n\
+             The port may also be pasted as <host>:<port>. \
+             Send just /addserver to be asked step by step.",
+            )
+            .await?;
             return Ok(());
         }
     };
 
-    match server_father.server_service().get_server(server_id).await {
-        Ok(Some(server)) => {
-            match server_father
-                .server_service()
-                .remove_server(server_id)
-                .await
-            {
-                Ok(true) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("✅ Server '{}' removed successfully!", server.name),
-                    )
+    let server = match new_server_from_args(name, host, port, &args) {
+        Ok(server) => server,
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ {}.", e)).await?;
+            return Ok(());
+        }
+    };
+
+    let group = match args.option("group") {
+        Some(group) => match find_group_by_name(&server_father, msg.chat.id, group).await? {
+            Some(group) => Some(group),
+            None => {
+                bot.send_message(msg.chat.id, format!("❌ Group '{}' not found.", group))
                     .await?;
-                }
-                Ok(false) => {
-                    bot.send_message(msg.chat.id, "❌ Server not found.")
-                        .await?;
-                }
-                Err(e) => {
-                    bot.send_message(msg.chat.id, format!("❌ Failed to remove server: {}", e))
-                        .await?;
-                }
+                return Ok(());
             }
+        },
+        None => None,
+    };
+
+    let note = describe_host(&server.host).await;
+    let message = save_new_server(&server_father, msg.chat.id, server, group.as_ref()).await;
+    bot.send_message(msg.chat.id, format!("{}\n{}", message, note.trim_end()))
+        .await?;
+    Ok(())
+}
+
+fn new_server_from_args(
+    name: &str,
+    host: &str,
+    port: Option<&str>,
+    args: &Args,
+) -> std::result::Result<NewServer, String> {
+    args.expect_options(ADD_SERVER_OPTIONS)?;
+
+    let input = host.parse::<HostInput>()?;
+    let port = match (input.port.map(i32::from), port) {
+        (Some(pasted), None) => pasted,
+        (None, Some(port)) => parse_port(port)?,
+        (Some(pasted), Some(port)) if parse_port(port)? == pasted => pasted,
+        (Some(_), Some(_)) => return Err("Two different ports given".to_string()),
+        (None, None) => {
+            return Err(format!(
+                "Missing port, give it after the host or as {}:<port>",
+                input.host
+            ))
         }
-        Ok(None) => {
-            bot.send_message(msg.chat.id, "❌ Server not found.")
-                .await?;
-        }
-        Err(e) => {
-            bot.send_message(msg.chat.id, format!("❌ Failed to fetch server: {}", e))
-                .await?;
+    };
+
+    let mut server = NewServer {
+        name: name.to_string(),
+        host: input.host,
+        port,
+        check_kind: CheckKind::default(),
+        http_check: None,
+        settings: CheckSettings::default(),
+    };
+    apply_server_options(&mut server, args)?;
+    Ok(server)
+}
+
+/// Looks up a group of the chat by ID or by name, ignoring case.
+async fn find_group_by_name(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    group: &str,
+) -> Result<Option<ServerGroupModel>> {
+    if let Ok(group_id) = group.parse::<i32>() {
+        return server_father
+            .group_service()
+            .get_group(chat_id.0, group_id)
+            .await;
+    }
+
+    let groups = server_father.group_service().list_groups(chat_id.0).await?;
+    Ok(groups
+        .into_iter()
+        .find(|candidate| candidate.name.eq_ignore_ascii_case(group)))
+}
+
+/// Applies the `key=value` options of /addserver and /editserver. The check
+/// settings take the same keys as in the wizard.
+fn apply_server_options(server: &mut NewServer, args: &Args) -> std::result::Result<(), String> {
+    let mut settings = Vec::new();
+    for (key, value) in args.options() {
+        match key.as_str() {
+            "name" if value.is_empty() => return Err("The name cannot be empty".to_string()),
+            "name" => server.name = value.to_string(),
+            "host" => {
+                let input = value.parse::<HostInput>()?;
+                server.host = input.host;
+                server.port = input.port.map_or(server.port, i32::from);
+            }
+            "port" => server.port = parse_port(value)?,
+            "type" => {
+                server.check_kind = parse_check_kind(value)
+                    .ok_or_else(|| format!("Invalid check type '{}'", value))?
+            }
+            "http" => server.http_check = Some(HttpCheck::parse_options(value)?),
+            // Joined once the server exists
+            "group" => {}
+            _ => settings.push(format!("{}={}", key, value)),
         }
     }
+    server.settings = server.settings.clone().apply(&settings.join(" "))?;
 
-    dialogue.update(State::Start).await?;
+    if matches!(server.check_kind, CheckKind::Http | CheckKind::Https) {
+        server.http_check.get_or_insert_with(HttpCheck::default);
+    } else {
+        server.http_check = None;
+    }
     Ok(())
 }
 
-async fn check_server(bot: Bot, server_father: Arc<ServerFatherBot>, msg: Message) -> Result<()> {
-    let args = msg
-        .text()
-        .unwrap_or_default()
-        .split_whitespace()
-        .collect::<Vec<_>>();
+fn parse_port(text: &str) -> std::result::Result<i32, String> {
+    text.trim()
+        .parse::<i32>()
+        .ok()
+        .filter(|port| (1..=65535).contains(port))
+        .ok_or_else(|| "Invalid port number, it has to be between 1 and 65535".to_string())
+}
+
+async fn receive_host(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+) -> Result<()> {
+    let input = match msg.text().unwrap_or_default().parse::<HostInput>() {
+        Ok(input) => input,
+        Err(e) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("❌ {}. Please enter the server host (IP or domain):", e),
+            )
+            .await?;
+            return Ok(());
+        }
+    };
 
-    if args.len() != 2 {
+    // A pasted host:port skips the port question
+    let Some(port) = input.port.map(i32::from) else {
+        let note = describe_host(&input.host).await;
+        dialogue
+            .update(State::AwaitingServerPort { name: input.host })
+            .await?;
         bot.send_message(
             msg.chat.id,
-            "Please provide a server ID (use /check <server_id>)",
+            format!("{}Please enter the port number:", note),
         )
         .await?;
         return Ok(());
-    }
-
-    let server_id = match args[1].parse::<i32>() {
-        Ok(id) => id,
-        Err(_) => {
-            bot.send_message(msg.chat.id, "Invalid server ID. Please enter a number.")
-                .await?;
-            return Ok(());
-        }
     };
 
-    match server_father.server_service().get_server(server_id).await {
-        Ok(Some(server)) => {
-            let is_up = server_father.check_server_status(&server).await?;
-            let status_emoji = if is_up { "🟢" } else { "🔴" };
-
-            bot.send_message(
-                msg.chat.id,
-                format!(
-                    "Server Status:\n{} *{}*\n`{}:{}`\nStatus: {}",
-                    status_emoji,
-                    server.name,
-                    server.host,
-                    server.port,
-                    if is_up { "Online" } else { "Offline" }
-                ),
-            )
-            .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-            .await?;
-        }
-        Ok(None) => {
-            bot.send_message(msg.chat.id, "❌ Server not found.")
-                .await?;
-        }
-        Err(e) => {
-            bot.send_message(msg.chat.id, format!("❌ Failed to fetch server: {}", e))
-                .await?;
-        }
+    if let Some(duplicate) =
+        duplicate_address(&server_father, msg.chat.id, &input.host, port, None).await?
+    {
+        bot.send_message(
+            msg.chat.id,
+            format!("❌ {}. Please enter another host:", duplicate),
+        )
+        .await?;
+        return Ok(());
     }
 
-    Ok(())
-}
-
-async fn create_group(bot: Bot, dialogue: MyDialogue, msg: Message) -> Result<()> {
-    dialogue.update(State::AwaitingGroupName).await?;
-
-    bot.send_message(msg.chat.id, "Please enter the name for the new group:")
+    let note = describe_host(&input.host).await;
+    dialogue
+        .update(State::AwaitingServerName {
+            host: input.host,
+            port,
+        })
         .await?;
+    bot.send_message(
+        msg.chat.id,
+        format!("{}Please enter a name for this server:", note),
+    )
+    .await?;
 
     Ok(())
 }
 
-async fn receive_group_name(
+async fn receive_port(
     bot: Bot,
     dialogue: MyDialogue,
     server_father: Arc<ServerFatherBot>,
     msg: Message,
+    state: State,
 ) -> Result<()> {
-    let name = msg.text().unwrap_or_default().to_string();
+    let Ok(port) = parse_port(msg.text().unwrap_or_default()) else {
+        bot.send_message(
+            msg.chat.id,
+            "Invalid port number. Please enter a number between 1 and 65535:",
+        )
+        .await?;
+        return Ok(());
+    };
 
-    match server_father
-        .group_service()
-        .create_group(name.clone())
-        .await
-    {
-        Ok(group) => {
+    if let State::AwaitingServerPort { name } = state {
+        if let Some(duplicate) =
+            duplicate_address(&server_father, msg.chat.id, &name, port, None).await?
+        {
             bot.send_message(
                 msg.chat.id,
-                format!(
-                    "✅ Group '{}' created successfully! (ID: {})",
-                    name, group.id
-                ),
+                format!("❌ {}. Please enter another port or /cancel:", duplicate),
             )
             .await?;
+            return Ok(());
         }
-        Err(e) => {
-            bot.send_message(msg.chat.id, format!("❌ Failed to create group: {}", e))
-                .await?;
-        }
+
+        dialogue
+            .update(State::AwaitingServerName { host: name, port })
+            .await?;
+
+        bot.send_message(msg.chat.id, "Please enter a name for this server:")
+            .await?;
     }
 
-    dialogue.update(State::Start).await?;
     Ok(())
 }
 
-async fn list_groups(bot: Bot, server_father: Arc<ServerFatherBot>, msg: Message) -> Result<()> {
-    match server_father.group_service().list_groups().await {
-        Ok(groups) => {
-            if groups.is_empty() {
-                bot.send_message(
-                    msg.chat.id,
-                    "No groups created yet. Use /creategroup to create one.",
-                )
-                .await?;
-                return Ok(());
-            }
-
-            let mut message = String::from("📁 *Server Groups*\n\n");
-            for group in groups {
-                let servers = server_father
-                    .server_service()
-                    .list_servers_by_group(group.id)
-                    .await?;
-
-                message.push_str(&format!(
-                    "👥 *{}* (ID: {})\nServers: {}\n\n",
-                    group.name,
-                    group.id,
-                    servers.len()
-                ));
-            }
+/// How long looking up a host may take while a server is added or edited.
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
 
-            bot.send_message(msg.chat.id, message)
-                .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-                .await?;
+/// Tells what a host resolves to, warning when it does not resolve or points
+/// into a private network. Every line ends with a newline, so it can go in
+/// front of the next question.
+async fn describe_host(host: &str) -> String {
+    let ips = match address::resolve(host, 0, RESOLVE_TIMEOUT).await {
+        Ok(ips) if !ips.is_empty() => ips,
+        Ok(_) => {
+            return format!(
+                "⚠️ '{}' has no addresses, checks fail until it resolves.\n",
+                host
+            )
         }
         Err(e) => {
-            bot.send_message(msg.chat.id, format!("❌ Failed to fetch groups: {}", e))
-                .await?;
+            return format!(
+                "⚠️ Could not resolve '{}' ({}), checks fail until it resolves.\n",
+                host, e
+            )
         }
+    };
+
+    let mut note = String::new();
+    if host.parse::<IpAddr>().is_err() {
+        let ips = ips.iter().map(IpAddr::to_string).collect::<Vec<_>>();
+        note.push_str(&format!("🔎 {} resolves to {}\n", host, ips.join(", ")));
     }
-    Ok(())
+
+    let private = ips
+        .iter()
+        .filter(|ip| address::is_private(**ip))
+        .map(IpAddr::to_string)
+        .collect::<Vec<_>>();
+    if !private.is_empty() {
+        note.push_str(&format!(
+            "⚠️ {} is private, checks only work while the bot runs in the same network.\n",
+            private.join(", ")
+        ));
+    }
+    note
+}
+
+/// Names the server of the chat that already watches `host` and `port`.
+async fn duplicate_address(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    host: &str,
+    port: i32,
+    except: Option<i32>,
+) -> Result<Option<String>> {
+    let server = server_father
+        .server_service()
+        .find_by_address(chat_id.0, host, port, except)
+        .await?;
+    Ok(server.map(|server| {
+        format!(
+            "Server '{}' (ID: {}) already watches {}",
+            server.name,
+            server.id,
+            address::join(host, port as u16)
+        )
+    }))
 }
 
-async fn add_to_group(bot: Bot, dialogue: MyDialogue, msg: Message) -> Result<()> {
-    dialogue.update(State::AwaitingGroupId).await?;
+async fn receive_name(bot: Bot, dialogue: MyDialogue, msg: Message, state: State) -> Result<()> {
+    let name = msg.text().unwrap_or_default().to_string();
 
-    bot.send_message(
-        msg.chat.id,
-        "Please enter the group ID (use /groups to see group IDs):",
-    )
-    .await?;
+    if let State::AwaitingServerName { host, port } = state {
+        dialogue
+            .update(State::AwaitingCheckKind { host, port, name })
+            .await?;
+
+        bot.send_message(
+            msg.chat.id,
+            "How should this server be checked? Reply with tcp, http, https or tls:",
+        )
+        .await?;
+    }
 
     Ok(())
 }
 
-async fn receive_group_id_for_server(bot: Bot, dialogue: MyDialogue, msg: Message) -> Result<()> {
-    let group_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
-        Some(id) => id,
+async fn receive_check_kind(
+    bot: Bot,
+    dialogue: MyDialogue,
+    msg: Message,
+    state: State,
+) -> Result<()> {
+    let kind = match parse_check_kind(msg.text().unwrap_or_default()) {
+        Some(kind) => kind,
         None => {
-            bot.send_message(msg.chat.id, "Invalid group ID. Please enter a number.")
-                .await?;
+            bot.send_message(
+                msg.chat.id,
+                "Invalid check type. Please reply with tcp, http, https or tls:",
+            )
+            .await?;
             return Ok(());
         }
     };
 
-    dialogue
-        .update(State::AwaitingServerForGroup { group_id })
-        .await?;
+    if let State::AwaitingCheckKind { host, port, name } = state {
+        if matches!(kind, CheckKind::Tcp | CheckKind::Tls) {
+            let server = NewServer {
+                name,
+                host,
+                port,
+                check_kind: kind,
+                http_check: None,
+                settings: CheckSettings::default(),
+            };
+            return ask_check_settings(bot, dialogue, msg.chat.id, server).await;
+        }
 
-    bot.send_message(
-        msg.chat.id,
-        "Please enter the server ID to add to this group (use /status to see server IDs):",
-    )
-    .await?;
+        dialogue
+            .update(State::AwaitingHttpOptions {
+                host,
+                port,
+                name,
+                kind,
+            })
+            .await?;
+
+        bot.send_message(
+            msg.chat.id,
+            "Send the HTTP check options as METHOD PATH STATUS REDIRECTS, e.g. \
+             \"HEAD /health 2xx follow\" or \"method=head path=/health status=200-299 redirects=follow\".\n\
+             Send \"default\" for GET / expecting 200-399 without following redirects.",
+        )
+        .await?;
+    }
 
     Ok(())
 }
 
-async fn receive_server_for_group(
+fn parse_check_kind(text: &str) -> Option<CheckKind> {
+    match text.trim().to_lowercase().as_str() {
+        "tcp" => Some(CheckKind::Tcp),
+        "http" => Some(CheckKind::Http),
+        "https" => Some(CheckKind::Https),
+        "tls" => Some(CheckKind::Tls),
+        _ => None,
+    }
+}
+
+async fn receive_http_options(
     bot: Bot,
     dialogue: MyDialogue,
-    server_father: Arc<ServerFatherBot>,
     msg: Message,
-    group_id: i32,
+    state: State,
 ) -> Result<()> {
-    let server_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
-        Some(id) => id,
-        None => {
-            bot.send_message(msg.chat.id, "Invalid server ID. Please enter a number.")
+    let http_check = match HttpCheck::parse_options(msg.text().unwrap_or_default()) {
+        Ok(http_check) => http_check,
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ {}. Please try again:", e))
                 .await?;
             return Ok(());
         }
     };
 
-    match server_father.server_service().get_server(server_id).await {
-        Ok(Some(server)) => {
-            match server_father
-                .server_service()
-                .assign_to_group(server_id, group_id)
-                .await
-            {
-                Ok(true) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("✅ Server '{}' added to group successfully!", server.name),
-                    )
-                    .await?;
-                }
-                Ok(false) => {
-                    bot.send_message(msg.chat.id, "❌ Failed to add server to group.")
-                        .await?;
-                }
-                Err(e) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        format!("❌ Error adding server to group: {}", e),
-                    )
-                    .await?;
-                }
-            }
-        }
-        Ok(None) => {
-            bot.send_message(msg.chat.id, "❌ Server not found.")
-                .await?;
-        }
-        Err(e) => {
-            bot.send_message(msg.chat.id, format!("❌ Error fetching server: {}", e))
-                .await?;
-        }
+    if let State::AwaitingHttpOptions {
+        host,
+        port,
+        name,
+        kind,
+    } = state
+    {
+        let server = NewServer {
+            name,
+            host,
+            port,
+            check_kind: kind,
+            http_check: Some(http_check),
+            settings: CheckSettings::default(),
+        };
+        ask_check_settings(bot, dialogue, msg.chat.id, server).await?;
     }
 
-    dialogue.update(State::Start).await?;
     Ok(())
 }
 
-async fn remove_group(bot: Bot, dialogue: MyDialogue, msg: Message) -> Result<()> {
-    dialogue.update(State::AwaitingGroupId).await?;
+async fn ask_check_settings(
+    bot: Bot,
+    dialogue: MyDialogue,
+    chat_id: ChatId,
+    server: NewServer,
+) -> Result<()> {
+    dialogue
+        .update(State::AwaitingCheckSettings { server })
+        .await?;
 
     bot.send_message(
-        msg.chat.id,
-        "Please enter the group ID to remove (use /groups to see group IDs):",
+        chat_id,
+        "Send check settings as key=value pairs, e.g. \"interval=60 timeout=5 retries=2 failures=3\" \
+         (interval and timeout in seconds, failures and recoveries are checks in a row \
+         before the server counts as down or up). \
+         family=ipv4|ipv6 limits checks to one address family, addresses=all checks every \
+         resolved address and reports a partial outage when one of them fails.\n\
+         Send \"default\" to use the global settings.",
     )
     .await?;
 
     Ok(())
 }
 
-async fn receive_group_id_for_removal(
+async fn receive_check_settings(
     bot: Bot,
     dialogue: MyDialogue,
     server_father: Arc<ServerFatherBot>,
     msg: Message,
+    server: NewServer,
 ) -> Result<()> {
-    let group_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
-        Some(id) => id,
-        None => {
-            bot.send_message(msg.chat.id, "Invalid group ID. Please enter a number.")
+    let settings = match CheckSettings::default().apply(msg.text().unwrap_or_default()) {
+        Ok(settings) => settings,
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ {}. Please try again:", e))
                 .await?;
             return Ok(());
         }
     };
 
-    // First check if group exists and get its name
-    let groups = server_father.group_service().list_groups().await?;
-    let group = groups.iter().find(|g| g.id == group_id);
-
-    match group {
-        Some(group) => match server_father.group_service().delete_group(group_id).await {
-            Ok(true) => {
-                bot.send_message(
-                    msg.chat.id,
-                    format!("✅ Group '{}' removed successfully!", group.name),
-                )
-                .await?;
-            }
-            Ok(false) => {
-                bot.send_message(msg.chat.id, "❌ Group not found.").await?;
-            }
-            Err(e) => {
-                bot.send_message(msg.chat.id, format!("❌ Failed to remove group: {}", e))
-                    .await?;
-            }
-        },
-        None => {
-            bot.send_message(msg.chat.id, "❌ Group not found.").await?;
-        }
-    }
+    let server = NewServer { settings, ..server };
+    let message = save_new_server(&server_father, msg.chat.id, server, None).await;
+    bot.send_message(msg.chat.id, message).await?;
 
     dialogue.update(State::Start).await?;
     Ok(())
 }
 
-async fn check_group(bot: Bot, server_father: Arc<ServerFatherBot>, msg: Message) -> Result<()> {
-    let args = msg
-        .text()
-        .unwrap_or_default()
-        .split_whitespace()
-        .collect::<Vec<_>>();
+/// Adds the server, to `group` as well when given.
+async fn save_new_server(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    server: NewServer,
+    group: Option<&ServerGroupModel>,
+) -> String {
+    let check_description = server
+        .http_check
+        .as_ref()
+        .map(|http_check| format!("\nCheck: {}", http_check))
+        .unwrap_or_default();
+
+    match duplicate_address(server_father, chat_id, &server.host, server.port, None).await {
+        Ok(Some(duplicate)) => return format!("❌ {}.", duplicate),
+        Ok(None) => {}
+        Err(e) => return format!("❌ Failed to add server: {}", e),
+    }
+
+    let server = match server_father
+        .server_service()
+        .add_server(chat_id.0, server)
+        .await
+    {
+        Ok(server) => server,
+        Err(e) => return format!("❌ Failed to add server: {}", e),
+    };
+
+    let membership = match group {
+        Some(group) => match server_father
+            .server_service()
+            .add_to_group(chat_id.0, server.id, group.id)
+            .await
+        {
+            Ok(_) => format!("\nGroup: {}", group.name),
+            Err(e) => format!("\n❌ Failed to add it to group '{}': {}", group.name, e),
+        },
+        None => String::new(),
+    };
+
+    format!(
+        "✅ Server '{}' ({}) added successfully! (ID: {}){}\nSettings: {}{}",
+        server.name,
+        server.target(),
+        server.id,
+        check_description,
+        CheckSettings::from_server(&server),
+        membership
+    )
+}
+
+async fn status(bot: Bot, server_father: Arc<ServerFatherBot>, msg: Message) -> Result<()> {
+    let servers = match server_father
+        .server_service()
+        .list_servers(msg.chat.id.0)
+        .await
+    {
+        Ok(servers) => servers,
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ Failed to fetch servers: {}", e))
+                .await?;
+            return Ok(());
+        }
+    };
 
-    if args.len() != 2 {
+    if servers.is_empty() {
         bot.send_message(
             msg.chat.id,
-            "Please provide a group ID (use /checkgroup <group_id>)",
+            "No servers added yet. Use /addserver to add one.",
         )
         .await?;
         return Ok(());
     }
 
-    let group_id = match args[1].parse::<i32>() {
-        Ok(id) => id,
-        Err(_) => {
-            bot.send_message(msg.chat.id, "Invalid group ID. Please enter a number.")
-                .await?;
-            return Ok(());
-        }
-    };
+    let mut status_message = String::from("📊 *Server Status*\n\n");
+    let outcomes = server_father.check_servers(&servers).await;
 
-    match server_father.group_service().list_groups().await {
-        Ok(groups) => {
-            let group = groups.iter().find(|g| g.id == group_id);
-
-            match group {
-                Some(group) => {
-                    let servers = server_father
-                        .server_service()
-                        .list_servers_by_group(group_id)
-                        .await?;
-
-                    if servers.is_empty() {
-                        bot.send_message(
-                            msg.chat.id,
-                            format!("Group '{}' has no servers.", group.name),
-                        )
-                        .await?;
-                        return Ok(());
-                    }
-
-                    let mut status_message = format!("📊 *Group: {}*\n\n", group.name);
-                    let mut total_up = 0;
-                    let total_servers = servers.len();
-
-                    for server in servers {
-                        let is_up = server_father
-                            .check_server_status(&server)
-                            .await
-                            .unwrap_or(false);
-
-                        if is_up {
-                            total_up += 1;
-                        }
-
-                        let status_emoji = if is_up { "🟢" } else { "🔴" };
-
-                        status_message.push_str(&format!(
-                            "{} *{}*\n`{}:{}`\n\n",
-                            status_emoji, server.name, server.host, server.port
-                        ));
-                    }
-
-                    // Add summary
-                    status_message.push_str(&format!(
-                        "Summary: {} of {} servers online",
-                        total_up, total_servers
-                    ));
-
-                    bot.send_message(msg.chat.id, status_message)
-                        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-                        .await?;
-                }
-                None => {
-                    bot.send_message(msg.chat.id, "❌ Group not found.").await?;
-                }
-            }
-        }
+    for (server, outcome) in servers.iter().zip(outcomes) {
+        let status_emoji = status_emoji(outcome.as_ref());
+        let escaped_name = escape_markdown(&server.name);
+        let escaped_target = escape_markdown(&server.target());
+        status_message.push_str(&format!(
+            "{} *{}* \\(ID: {}\\)\n`{}`\n\n",
+            status_emoji, escaped_name, server.id, escaped_target
+        ));
+    }
+
+    bot.send_message(msg.chat.id, status_message)
+        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+        .await?;
+
+    Ok(())
+}
+
+/// Escapes text for MarkdownV2 messages.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\_*[]()~`>#+-=|{}.!".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// 🟢 online, 🔴 offline, ⚪ no verdict within the check deadline.
+fn status_emoji(outcome: Option<&CheckOutcome>) -> &'static str {
+    match outcome {
+        Some(outcome) if outcome.is_up => "🟢",
+        Some(_) => "🔴",
+        None => "⚪",
+    }
+}
+
+async fn invalid_state(bot: Bot, msg: Message, state: State) -> Result<()> {
+    let message = match state {
+        State::Start => "⚠️ Unknown command. Try /start".to_string(),
+        state => format!(
+            "⚠️ I expected an answer for {}. Send it as text, or /cancel to stop.",
+            state.activity()
+        ),
+    };
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+async fn invalid_args(bot: Bot, msg: Message, error: String) -> Result<()> {
+    bot.send_message(msg.chat.id, format!("❌ {}.", error))
+        .await?;
+    Ok(())
+}
+
+async fn cancel(bot: Bot, dialogue: MyDialogue, msg: Message, state: State) -> Result<()> {
+    let message = match state {
+        State::Start => "Nothing to cancel.".to_string(),
+        state => {
+            dialogue.update(State::Start).await?;
+            format!("❌ Stopped {}.", state.activity())
+        }
+    };
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+/// Resets the dialogue when a command comes in the middle of a wizard, the
+/// command then runs as if it was sent from the start.
+async fn leave_wizard(bot: Bot, dialogue: MyDialogue, msg: Message, state: State) -> State {
+    if matches!(state, State::Start) {
+        return state;
+    }
+
+    if let Err(e) = dialogue.update(State::Start).await {
+        tracing::warn!("Failed to reset dialogue of chat {}: {}", msg.chat.id, e);
+    }
+    let message = format!("↩️ Stopped {}.", state.activity());
+    if let Err(e) = bot.send_message(msg.chat.id, message).await {
+        tracing::warn!("Failed to send message to chat {}: {}", msg.chat.id, e);
+    }
+
+    State::Start
+}
+
+/// Ends wizards left idle for longer than the storage's TTL and tells the chat.
+pub async fn expire_dialogues(bot: Bot, dialogues: Arc<StateStorage>) {
+    let mut interval = tokio::time::interval(DIALOGUE_SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        let expired = match dialogues.take_expired().await {
+            Ok(expired) => expired,
+            Err(e) => {
+                tracing::warn!("Failed to expire dialogues: {}", e);
+                continue;
+            }
+        };
+
+        for (chat_id, state) in expired {
+            if matches!(state, State::Start) {
+                continue;
+            }
+            let message = format!(
+                "⌛ Stopped {} after a while without an answer. Send the command again to start over.",
+                state.activity()
+            );
+            if let Err(e) = bot.send_message(chat_id, message).await {
+                tracing::warn!("Failed to send message to chat {}: {}", chat_id, e);
+            }
+        }
+    }
+}
+
+async fn start_monitoring(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let scope = match parse_monitor_scope(args.words().first().copied()) {
+        Ok(scope) => scope.unwrap_or(MonitorScope::All),
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ {}", e)).await?;
+            return Ok(());
+        }
+    };
+
+    let interval = match args.words().get(1) {
+        Some(arg) => match parse_interval(arg) {
+            Ok(interval) => Some(interval),
+            Err(e) => {
+                bot.send_message(msg.chat.id, format!("❌ {}", e)).await?;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+
+    if let MonitorScope::Group(group_id) = scope {
+        if server_father
+            .group_service()
+            .get_group(msg.chat.id.0, group_id)
+            .await?
+            .is_none()
+        {
+            bot.send_message(msg.chat.id, "❌ Group not found.").await?;
+            return Ok(());
+        }
+    }
+
+    let description = server_father.describe_scope(msg.chat.id, scope).await;
+    match server_father
+        .start_monitoring(msg.chat.id, scope, interval)
+        .await
+    {
+        Ok(MonitorStart::Started) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Monitoring started for {}! You'll receive notifications when server status changes.",
+                    description
+                ),
+            )
+            .await?;
+        }
+        Ok(MonitorStart::Resumed) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("▶️ Monitoring resumed for {}.", description),
+            )
+            .await?;
+        }
+        Ok(MonitorStart::AlreadyRunning) => {
+            bot.send_message(
+                msg.chat.id,
+                format!("ℹ️ Already monitoring {}. See /monitors.", description),
+            )
+            .await?;
+        }
+        Ok(MonitorStart::Overlaps(running)) => {
+            let stop_arg = match running {
+                MonitorScope::All => "all".to_string(),
+                MonitorScope::Group(group_id) => group_id.to_string(),
+            };
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "❌ Monitoring {} already, which overlaps {}. Stop it first with /stopmonitor {}.",
+                    server_father.describe_scope(msg.chat.id, running).await,
+                    description,
+                    stop_arg
+                ),
+            )
+            .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ Failed to start monitoring: {}", e))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn stop_monitoring(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let scope = match parse_monitor_scope(args.words().first().copied()) {
+        Ok(scope) => scope,
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ {}", e)).await?;
+            return Ok(());
+        }
+    };
+
+    let stopped = server_father.stop_monitoring(msg.chat.id, scope).await?;
+    let message = if stopped == 0 {
+        "ℹ️ No matching monitor is running. See /monitors.".to_string()
+    } else {
+        format!("⏹ Stopped {} monitor(s).", stopped)
+    };
+
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+async fn pause_monitoring(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let scope = match parse_monitor_scope(args.words().first().copied()) {
+        Ok(scope) => scope,
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ {}", e)).await?;
+            return Ok(());
+        }
+    };
+
+    let paused = server_father.pause_monitoring(msg.chat.id, scope).await?;
+    let message = if paused == 0 {
+        "ℹ️ No matching monitor is running. See /monitors.".to_string()
+    } else {
+        format!(
+            "⏸ Paused {} monitor(s). Send /monitor again to resume.",
+            paused
+        )
+    };
+
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+async fn list_monitors(bot: Bot, server_father: Arc<ServerFatherBot>, msg: Message) -> Result<()> {
+    let mut monitors = server_father.list_monitors(msg.chat.id).await;
+
+    if monitors.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "No monitors running. Use /monitor to start one.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    monitors.sort_by_key(|monitor| monitor.started_at);
+    let mut message = String::from("📡 Monitors\n\n");
+    for monitor in monitors {
+        let interval = monitor
+            .control
+            .interval
+            .map(|interval| format!(", every {} seconds", interval.as_secs()))
+            .unwrap_or_default();
+        message.push_str(&format!(
+            "{} {} (since {}{})\n",
+            if monitor.control.paused {
+                "⏸"
+            } else {
+                "▶️"
+            },
+            server_father
+                .describe_scope(msg.chat.id, monitor.scope)
+                .await,
+            monitor.started_at.format("%Y-%m-%d %H:%M UTC"),
+            interval
+        ));
+    }
+
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+/// Reads the optional scope argument: a group ID or `all` for every server.
+fn parse_monitor_scope(arg: Option<&str>) -> std::result::Result<Option<MonitorScope>, String> {
+    match arg {
+        None => Ok(None),
+        Some(arg) if arg.eq_ignore_ascii_case("all") => Ok(Some(MonitorScope::All)),
+        Some(arg) => arg
+            .parse::<i32>()
+            .map(|group_id| Some(MonitorScope::Group(group_id)))
+            .map_err(|_| "Invalid group ID. Please enter a number or \"all\".".to_string()),
+    }
+}
+
+async fn remove_server(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    if let [server_id] = args.words()[..] {
+        let Some(server_id) = parse_id(&bot, msg.chat.id, server_id, "server").await? else {
+            return Ok(());
+        };
+        let reply = server_removal_prompt(&server_father, msg.chat.id, server_id).await?;
+        return send_reply(&bot, msg.chat.id, reply).await;
+    }
+
+    let servers = server_father
+        .server_service()
+        .list_servers(msg.chat.id.0)
+        .await?;
+    if servers.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "No servers added yet. Use /addserver to add one.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    dialogue.update(State::AwaitingServerId).await?;
+
+    let keyboard = pick_keyboard(&servers, |server| {
+        Action::RemoveServer(server.id).button(format!("{} ({})", server.name, server.target()))
+    });
+    bot.send_message(
+        msg.chat.id,
+        "Which server should be removed? Pick one or type its ID:",
+    )
+    .reply_markup(keyboard)
+    .await?;
+
+    Ok(())
+}
+
+async fn receive_server_id(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+) -> Result<()> {
+    let server_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
+        Some(id) => id,
+        None => {
+            bot.send_message(msg.chat.id, "Invalid server ID. Please enter a number.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let (text, keyboard) = server_removal_prompt(&server_father, msg.chat.id, server_id).await?;
+    let reply = bot.send_message(msg.chat.id, text);
+    match keyboard {
+        Some(keyboard) => reply.reply_markup(keyboard).await?,
+        None => reply.await?,
+    };
+
+    dialogue.update(State::Start).await?;
+    Ok(())
+}
+
+/// Asks before a server is removed.
+async fn server_removal_prompt(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    server_id: i32,
+) -> Result<Reply> {
+    let server = server_father
+        .server_service()
+        .get_server(chat_id.0, server_id)
+        .await?;
+
+    Ok(match server {
+        Some(server) => (
+            format!(
+                "Remove server '{}' ({}) and its check history?",
+                server.name,
+                server.target()
+            ),
+            Some(confirm_keyboard(
+                Action::ConfirmRemoveServer(server.id),
+                "🗑 Remove",
+            )),
+        ),
+        None => ("❌ Server not found.".to_string(), None),
+    })
+}
+
+async fn remove_server_by_id(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    server_id: i32,
+) -> String {
+    let server = match server_father
+        .server_service()
+        .get_server(chat_id.0, server_id)
+        .await
+    {
+        Ok(Some(server)) => server,
+        Ok(None) => return "❌ Server not found.".to_string(),
+        Err(e) => return format!("❌ Failed to fetch server: {}", e),
+    };
+
+    match server_father
+        .server_service()
+        .remove_server(chat_id.0, server_id)
+        .await
+    {
+        Ok(true) => format!("✅ Server '{}' removed successfully!", server.name),
+        Ok(false) => "❌ Server not found.".to_string(),
+        Err(e) => format!("❌ Failed to remove server: {}", e),
+    }
+}
+
+async fn check_server(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let [server_id] = args.words()[..] else {
+        bot.send_message(
+            msg.chat.id,
+            "Please provide a server ID (use /check <server_id>)",
+        )
+        .await?;
+        return Ok(());
+    };
+    let Some(server_id) = parse_id(&bot, msg.chat.id, server_id, "server").await? else {
+        return Ok(());
+    };
+
+    match server_father
+        .server_service()
+        .get_server(msg.chat.id.0, server_id)
+        .await
+    {
+        Ok(Some(server)) => {
+            let outcome = server_father.check_server_status(&server).await?;
+            let status_emoji = if outcome.is_up { "🟢" } else { "🔴" };
+            let latency = outcome
+                .latency
+                .map(|latency| format!("\nLatency: {} ms", latency.as_millis()))
+                .unwrap_or_default();
+            let certificate = outcome
+                .certificate
+                .map(|certificate| {
+                    format!(
+                        "\nCertificate expires: `{} ({} days)`",
+                        certificate.not_after.format("%Y-%m-%d"),
+                        certificate.days_left(Utc::now().naive_utc())
+                    )
+                })
+                .unwrap_or_default();
+            let addresses = match outcome.addresses.len() {
+                0 => String::new(),
+                total => {
+                    let up = outcome.addresses.iter().filter(|a| a.is_up).count();
+                    let lines = outcome
+                        .addresses
+                        .iter()
+                        .map(|address| {
+                            format!(
+                                "\n{} `{}`",
+                                if address.is_up { "🟢" } else { "🔴" },
+                                address.describe()
+                            )
+                        })
+                        .collect::<String>();
+                    format!("\nAddresses up: {} of {}{}", up, total, lines)
+                }
+            };
+
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "Server Status:\n{} *{}*\n`{}`\nStatus: {}{}{}{}",
+                    status_emoji,
+                    escape_markdown(&server.name),
+                    escape_markdown(&server.target()),
+                    match (outcome.is_up, outcome.error) {
+                        (true, _) => "Online",
+                        (false, Some(ErrorKind::Partial)) => "Partially offline",
+                        (false, _) => "Offline",
+                    },
+                    latency,
+                    certificate,
+                    addresses
+                ),
+            )
+            .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+            .await?;
+        }
+        Ok(None) => {
+            bot.send_message(msg.chat.id, "❌ Server not found.")
+                .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ Failed to fetch server: {}", e))
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn create_group(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    if !args.is_empty() {
+        let name = args.values().join(" ");
+        let message = match args.expect_options(&["description"]) {
+            _ if name.is_empty() => "❌ Please give the group a name.".to_string(),
+            Ok(()) => {
+                let description = args.option("description").map(str::to_string);
+                save_new_group(&server_father, msg.chat.id, name, description).await
+            }
+            Err(e) => format!("❌ {}.", e),
+        };
+        bot.send_message(msg.chat.id, message).await?;
+        return Ok(());
+    }
+
+    dialogue.update(State::AwaitingGroupName).await?;
+
+    bot.send_message(msg.chat.id, "Please enter the name for the new group:")
+        .await?;
+
+    Ok(())
+}
+
+async fn receive_group_name(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+) -> Result<()> {
+    let name = msg.text().unwrap_or_default().to_string();
+    let message = save_new_group(&server_father, msg.chat.id, name, None).await;
+    bot.send_message(msg.chat.id, message).await?;
+
+    dialogue.update(State::Start).await?;
+    Ok(())
+}
+
+async fn save_new_group(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    name: String,
+    description: Option<String>,
+) -> String {
+    let group = match server_father
+        .group_service()
+        .create_group(chat_id.0, name)
+        .await
+    {
+        Ok(group) => group,
+        Err(e) => return format!("❌ Failed to create group: {}", e),
+    };
+
+    if description.is_some() {
+        if let Err(e) = server_father
+            .group_service()
+            .set_description(chat_id.0, group.id, description)
+            .await
+        {
+            return format!(
+                "✅ Group '{}' created (ID: {}), but its description was not saved: {}",
+                group.name, group.id, e
+            );
+        }
+    }
+
+    format!(
+        "✅ Group '{}' created successfully! (ID: {})",
+        group.name, group.id
+    )
+}
+
+async fn list_groups(bot: Bot, server_father: Arc<ServerFatherBot>, msg: Message) -> Result<()> {
+    match server_father
+        .group_service()
+        .list_groups(msg.chat.id.0)
+        .await
+    {
+        Ok(groups) => {
+            if groups.is_empty() {
+                bot.send_message(
+                    msg.chat.id,
+                    "No groups created yet. Use /creategroup to create one.",
+                )
+                .await?;
+                return Ok(());
+            }
+
+            let mut message = String::from("📁 *Server Groups*\n\n");
+            for group in groups {
+                let servers = server_father
+                    .server_service()
+                    .list_servers_by_group(msg.chat.id.0, group.id)
+                    .await?;
+
+                let members = if servers.is_empty() {
+                    "none".to_string()
+                } else {
+                    servers
+                        .iter()
+                        .map(|server| escape_markdown(&server.name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                message.push_str(&format!(
+                    "👥 *{}* \\(ID: {}\\)\n",
+                    escape_markdown(&group.name),
+                    group.id
+                ));
+                if let Some(description) = &group.description {
+                    message.push_str(&format!("_{}_\n", escape_markdown(description)));
+                }
+                message.push_str(&format!("Servers \\({}\\): {}\n\n", servers.len(), members));
+            }
+
+            bot.send_message(msg.chat.id, message)
+                .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+                .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ Failed to fetch groups: {}", e))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn add_to_group(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    match args.words()[..] {
+        [] => {}
+        [group_id] => {
+            let Some(group_id) = parse_id(&bot, msg.chat.id, group_id, "group").await? else {
+                return Ok(());
+            };
+            let reply = server_picker(&server_father, msg.chat.id, group_id).await?;
+            if reply.1.is_some() {
+                dialogue
+                    .update(State::AwaitingServerForGroup { group_id })
+                    .await?;
+            }
+            return send_reply(&bot, msg.chat.id, reply).await;
+        }
+        [group_id, server_id] => {
+            let Some(group_id) = parse_id(&bot, msg.chat.id, group_id, "group").await? else {
+                return Ok(());
+            };
+            let Some(server_id) = parse_id(&bot, msg.chat.id, server_id, "server").await? else {
+                return Ok(());
+            };
+            let message =
+                add_server_to_group(&server_father, msg.chat.id, group_id, server_id).await;
+            bot.send_message(msg.chat.id, message).await?;
+            return Ok(());
+        }
+        _ => {
+            bot.send_message(msg.chat.id, "Usage: /addtogroup [<group_id> [<server_id>]]")
+                .await?;
+            return Ok(());
+        }
+    }
+
+    let Some(keyboard) = group_picker(&bot, &server_father, msg.chat.id, Action::PickGroup).await?
+    else {
+        return Ok(());
+    };
+
+    dialogue.update(State::AwaitingGroupIdForServer).await?;
+
+    bot.send_message(
+        msg.chat.id,
+        "Which group should the server join? Pick one or type its ID:",
+    )
+    .reply_markup(keyboard)
+    .await?;
+
+    Ok(())
+}
+
+/// Buttons for every group of the chat, or `None` after telling there are none.
+async fn group_picker(
+    bot: &Bot,
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    action: fn(i32) -> Action,
+) -> Result<Option<InlineKeyboardMarkup>> {
+    let groups = server_father.group_service().list_groups(chat_id.0).await?;
+    if groups.is_empty() {
+        bot.send_message(
+            chat_id,
+            "No groups created yet. Use /creategroup to create one.",
+        )
+        .await?;
+        return Ok(None);
+    }
+
+    Ok(Some(pick_keyboard(&groups, |group| {
+        action(group.id).button(format!("{} (ID: {})", group.name, group.id))
+    })))
+}
+
+async fn receive_group_id_for_server(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+) -> Result<()> {
+    let group_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
+        Some(id) => id,
+        None => {
+            bot.send_message(msg.chat.id, "Invalid group ID. Please enter a number.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if server_father
+        .group_service()
+        .get_group(msg.chat.id.0, group_id)
+        .await?
+        .is_none()
+    {
+        bot.send_message(
+            msg.chat.id,
+            "❌ Group not found. Please enter a group ID from /groups:",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let (text, keyboard) = server_picker(&server_father, msg.chat.id, group_id).await?;
+    let next_state = match keyboard {
+        Some(_) => State::AwaitingServerForGroup { group_id },
+        None => State::Start,
+    };
+    dialogue.update(next_state).await?;
+
+    let reply = bot.send_message(msg.chat.id, text);
+    match keyboard {
+        Some(keyboard) => reply.reply_markup(keyboard).await?,
+        None => reply.await?,
+    };
+
+    Ok(())
+}
+
+/// Offers the chat's servers that are not in the group yet.
+async fn server_picker(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    group_id: i32,
+) -> Result<Reply> {
+    let Some(group) = server_father
+        .group_service()
+        .get_group(chat_id.0, group_id)
+        .await?
+    else {
+        return Ok(("❌ Group not found.".to_string(), None));
+    };
+
+    let members = server_father
+        .server_service()
+        .list_servers_by_group(chat_id.0, group_id)
+        .await?;
+    let servers = server_father
+        .server_service()
+        .list_servers(chat_id.0)
+        .await?
+        .into_iter()
+        .filter(|server| !members.iter().any(|member| member.id == server.id))
+        .collect::<Vec<_>>();
+    if servers.is_empty() {
+        return Ok((format!("No servers left to add to '{}'.", group.name), None));
+    }
+
+    let keyboard = pick_keyboard(&servers, |server| {
+        Action::AddToGroup {
+            group_id,
+            server_id: server.id,
+        }
+        .button(format!("{} ({})", server.name, server.target()))
+    });
+    Ok((
+        format!(
+            "Which server should join '{}'? Pick one or type its ID:",
+            group.name
+        ),
+        Some(keyboard),
+    ))
+}
+
+async fn receive_server_for_group(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    group_id: i32,
+) -> Result<()> {
+    let server_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
+        Some(id) => id,
+        None => {
+            bot.send_message(msg.chat.id, "Invalid server ID. Please enter a number.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let message = add_server_to_group(&server_father, msg.chat.id, group_id, server_id).await;
+    bot.send_message(msg.chat.id, message).await?;
+
+    dialogue.update(State::Start).await?;
+    Ok(())
+}
+
+async fn add_server_to_group(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    group_id: i32,
+    server_id: i32,
+) -> String {
+    let server = match server_father
+        .server_service()
+        .get_server(chat_id.0, server_id)
+        .await
+    {
+        Ok(Some(server)) => server,
+        Ok(None) => return "❌ Server not found.".to_string(),
+        Err(e) => return format!("❌ Error fetching server: {}", e),
+    };
+
+    match server_father
+        .server_service()
+        .add_to_group(chat_id.0, server_id, group_id)
+        .await
+    {
+        Ok(true) => format!("✅ Server '{}' added to group successfully!", server.name),
+        Ok(false) => "❌ Failed to add server to group.".to_string(),
+        Err(e) => format!("❌ Error adding server to group: {}", e),
+    }
+}
+
+async fn remove_from_group(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    match args.words()[..] {
+        [] => {}
+        [group_id] => {
+            let Some(group_id) = parse_id(&bot, msg.chat.id, group_id, "group").await? else {
+                return Ok(());
+            };
+            let reply = member_picker(&server_father, msg.chat.id, group_id).await?;
+            if reply.1.is_some() {
+                dialogue
+                    .update(State::AwaitingServerToUnassign { group_id })
+                    .await?;
+            }
+            return send_reply(&bot, msg.chat.id, reply).await;
+        }
+        [group_id, server_id] => {
+            let Some(group_id) = parse_id(&bot, msg.chat.id, group_id, "group").await? else {
+                return Ok(());
+            };
+            let Some(server_id) = parse_id(&bot, msg.chat.id, server_id, "server").await? else {
+                return Ok(());
+            };
+            let message =
+                remove_server_from_group(&server_father, msg.chat.id, group_id, server_id).await;
+            bot.send_message(msg.chat.id, message).await?;
+            return Ok(());
+        }
+        _ => {
+            bot.send_message(
+                msg.chat.id,
+                "Usage: /removefromgroup [<group_id> [<server_id>]]",
+            )
+            .await?;
+            return Ok(());
+        }
+    }
+
+    let Some(keyboard) =
+        group_picker(&bot, &server_father, msg.chat.id, Action::PickGroupToLeave).await?
+    else {
+        return Ok(());
+    };
+
+    dialogue.update(State::AwaitingGroupIdForUnassign).await?;
+
+    bot.send_message(
+        msg.chat.id,
+        "Which group should the server leave? Pick one or type its ID:",
+    )
+    .reply_markup(keyboard)
+    .await?;
+
+    Ok(())
+}
+
+async fn receive_group_id_for_unassign(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+) -> Result<()> {
+    let group_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
+        Some(id) => id,
+        None => {
+            bot.send_message(msg.chat.id, "Invalid group ID. Please enter a number.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let (text, keyboard) = member_picker(&server_father, msg.chat.id, group_id).await?;
+    let next_state = match keyboard {
+        Some(_) => State::AwaitingServerToUnassign { group_id },
+        None => State::Start,
+    };
+    dialogue.update(next_state).await?;
+
+    let reply = bot.send_message(msg.chat.id, text);
+    match keyboard {
+        Some(keyboard) => reply.reply_markup(keyboard).await?,
+        None => reply.await?,
+    };
+
+    Ok(())
+}
+
+/// Offers the servers of a group.
+async fn member_picker(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    group_id: i32,
+) -> Result<Reply> {
+    let Some(group) = server_father
+        .group_service()
+        .get_group(chat_id.0, group_id)
+        .await?
+    else {
+        return Ok(("❌ Group not found.".to_string(), None));
+    };
+
+    let members = server_father
+        .server_service()
+        .list_servers_by_group(chat_id.0, group_id)
+        .await?;
+    if members.is_empty() {
+        return Ok((format!("Group '{}' has no servers.", group.name), None));
+    }
+
+    let keyboard = pick_keyboard(&members, |server| {
+        Action::RemoveFromGroup {
+            group_id,
+            server_id: server.id,
+        }
+        .button(format!("{} ({})", server.name, server.target()))
+    });
+    Ok((
+        format!(
+            "Which server should leave '{}'? Pick one or type its ID:",
+            group.name
+        ),
+        Some(keyboard),
+    ))
+}
+
+async fn receive_server_to_unassign(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    group_id: i32,
+) -> Result<()> {
+    let server_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
+        Some(id) => id,
+        None => {
+            bot.send_message(msg.chat.id, "Invalid server ID. Please enter a number.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let message = remove_server_from_group(&server_father, msg.chat.id, group_id, server_id).await;
+    bot.send_message(msg.chat.id, message).await?;
+
+    dialogue.update(State::Start).await?;
+    Ok(())
+}
+
+async fn remove_server_from_group(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    group_id: i32,
+    server_id: i32,
+) -> String {
+    let server = match server_father
+        .server_service()
+        .get_server(chat_id.0, server_id)
+        .await
+    {
+        Ok(Some(server)) => server,
+        Ok(None) => return "❌ Server not found.".to_string(),
+        Err(e) => return format!("❌ Error fetching server: {}", e),
+    };
+
+    match server_father
+        .server_service()
+        .remove_from_group(chat_id.0, server_id, group_id)
+        .await
+    {
+        Ok(true) => format!("✅ Server '{}' removed from the group.", server.name),
+        Ok(false) => format!("❌ Server '{}' is not in this group.", server.name),
+        Err(e) => format!("❌ Error removing server from group: {}", e),
+    }
+}
+
+async fn remove_group(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    if let [group_id] = args.words()[..] {
+        let Some(group_id) = parse_id(&bot, msg.chat.id, group_id, "group").await? else {
+            return Ok(());
+        };
+        let reply = group_removal_prompt(&server_father, msg.chat.id, group_id).await?;
+        return send_reply(&bot, msg.chat.id, reply).await;
+    }
+
+    let Some(keyboard) =
+        group_picker(&bot, &server_father, msg.chat.id, Action::RemoveGroup).await?
+    else {
+        return Ok(());
+    };
+
+    dialogue.update(State::AwaitingGroupIdForRemoval).await?;
+
+    bot.send_message(
+        msg.chat.id,
+        "Which group should be removed? Pick one or type its ID:",
+    )
+    .reply_markup(keyboard)
+    .await?;
+
+    Ok(())
+}
+
+async fn receive_group_id_for_removal(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+) -> Result<()> {
+    let group_id = match msg.text().and_then(|text| text.parse::<i32>().ok()) {
+        Some(id) => id,
+        None => {
+            bot.send_message(msg.chat.id, "Invalid group ID. Please enter a number.")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let (text, keyboard) = group_removal_prompt(&server_father, msg.chat.id, group_id).await?;
+    let reply = bot.send_message(msg.chat.id, text);
+    match keyboard {
+        Some(keyboard) => reply.reply_markup(keyboard).await?,
+        None => reply.await?,
+    };
+
+    dialogue.update(State::Start).await?;
+    Ok(())
+}
+
+/// Asks before a group is removed, its servers stay.
+async fn group_removal_prompt(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    group_id: i32,
+) -> Result<Reply> {
+    let Some(group) = server_father
+        .group_service()
+        .get_group(chat_id.0, group_id)
+        .await?
+    else {
+        return Ok(("❌ Group not found.".to_string(), None));
+    };
+
+    let servers = server_father
+        .server_service()
+        .list_servers_by_group(chat_id.0, group_id)
+        .await?;
+    if servers.is_empty() {
+        return Ok((
+            format!("Remove the empty group '{}'?", group.name),
+            Some(confirm_keyboard(
+                Action::ConfirmRemoveGroup(group.id),
+                "🗑 Remove",
+            )),
+        ));
+    }
+
+    let keyboard = InlineKeyboardMarkup::new([
+        vec![Action::ConfirmRemoveGroup(group.id).button("🗑 Remove the group, keep its servers")],
+        vec![
+            Action::ConfirmRemoveGroupWithServers(group.id).button(format!(
+                "💣 Remove the group and its {} server(s)",
+                servers.len()
+            )),
+        ],
+        vec![Action::Cancel.button("✖️ Cancel")],
+    ]);
+    Ok((
+        format!(
+            "Remove group '{}'? It has {} server(s), removing them as well deletes \
+             them everywhere, also from other groups.",
+            group.name,
+            servers.len()
+        ),
+        Some(keyboard),
+    ))
+}
+
+async fn remove_group_by_id(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    group_id: i32,
+) -> String {
+    let group = match server_father
+        .group_service()
+        .get_group(chat_id.0, group_id)
+        .await
+    {
+        Ok(Some(group)) => group,
+        Ok(None) => return "❌ Group not found.".to_string(),
+        Err(e) => return format!("❌ Failed to fetch group: {}", e),
+    };
+
+    match server_father
+        .group_service()
+        .delete_group(chat_id.0, group_id)
+        .await
+    {
+        Ok(true) => format!("✅ Group '{}' removed successfully!", group.name),
+        Ok(false) => "❌ Group not found.".to_string(),
+        Err(e) => format!("❌ Failed to remove group: {}", e),
+    }
+}
+
+async fn remove_group_with_servers(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    group_id: i32,
+) -> String {
+    let group = match server_father
+        .group_service()
+        .get_group(chat_id.0, group_id)
+        .await
+    {
+        Ok(Some(group)) => group,
+        Ok(None) => return "❌ Group not found.".to_string(),
+        Err(e) => return format!("❌ Failed to fetch group: {}", e),
+    };
+
+    match server_father
+        .group_service()
+        .delete_group_with_servers(chat_id.0, group_id)
+        .await
+    {
+        Ok(Some(servers)) => format!(
+            "✅ Group '{}' and {} server(s) removed successfully!",
+            group.name, servers
+        ),
+        Ok(None) => "❌ Group not found.".to_string(),
+        Err(e) => format!("❌ Failed to remove group: {}", e),
+    }
+}
+
+async fn show_group(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let [group_id] = args.words()[..] else {
+        bot.send_message(
+            msg.chat.id,
+            "Please provide a group ID (use /group <group_id>)",
+        )
+        .await?;
+        return Ok(());
+    };
+    let Some(group_id) = parse_id(&bot, msg.chat.id, group_id, "group").await? else {
+        return Ok(());
+    };
+
+    let Some((group, servers)) = find_group(&bot, &server_father, msg.chat.id, group_id).await?
+    else {
+        return Ok(());
+    };
+
+    let mut message = format!("👥 {} (ID: {})\n", group.name, group.id);
+    if let Some(description) = &group.description {
+        message.push_str(&format!("{}\n", description));
+    }
+
+    let channels = [
+        group.webhook_url.as_ref().map(|_| "webhook"),
+        (!group.email_recipients().is_empty()).then_some("mail"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if !channels.is_empty() {
+        message.push_str(&format!("Alerts also go to: {}\n", channels.join(", ")));
+    }
+
+    if servers.is_empty() {
+        message.push_str("\nNo servers yet. Use /addtogroup to add one.");
+    } else {
+        message.push_str(&format!("\nServers ({}):\n", servers.len()));
+        let now = Utc::now().naive_utc();
+        for server in &servers {
+            let state = match server.last_status {
+                Some(true) => "🟢",
+                Some(false) => "🔴",
+                None => "⚪",
+            };
+            let last_check = server
+                .last_check
+                .map(|checked| {
+                    format!(
+                        "checked {} ago",
+                        format_span((now - checked).to_std().unwrap_or_default())
+                    )
+                })
+                .unwrap_or_else(|| "never checked".to_string());
+            message.push_str(&format!(
+                "{} {} (ID: {}) {}, {}\n",
+                state,
+                server.name,
+                server.id,
+                server.target(),
+                last_check
+            ));
+        }
+    }
+
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+async fn rename_group(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let words = args.words();
+    let group_id = words.first().and_then(|id| id.parse::<i32>().ok());
+    let name = words.get(1..).map(|name| name.join(" "));
+    let name = name.as_deref().filter(|name| !name.is_empty());
+
+    let (Some(group_id), Some(name)) = (group_id, name) else {
+        bot.send_message(msg.chat.id, "Usage: /renamegroup <group_id> <new name>")
+            .await?;
+        return Ok(());
+    };
+
+    let message = match server_father
+        .group_service()
+        .rename_group(msg.chat.id.0, group_id, name.to_string())
+        .await
+    {
+        Ok(Some(group)) => format!("✅ Group {} renamed to '{}'.", group.id, group.name),
+        Ok(None) => "❌ Group not found.".to_string(),
+        Err(e) => format!("❌ Failed to rename group: {}", e),
+    };
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+async fn describe_group(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let words = args.words();
+    let group_id = words.first().and_then(|id| id.parse::<i32>().ok());
+    let description = words.get(1..).map(|text| text.join(" "));
+    let description = description.as_deref().filter(|text| !text.is_empty());
+
+    let (Some(group_id), Some(description)) = (group_id, description) else {
+        bot.send_message(
+            msg.chat.id,
+            "Usage: /describegroup <group_id> <description> or /describegroup <group_id> off",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let description = (description != "off").then(|| description.to_string());
+    let message = match server_father
+        .group_service()
+        .set_description(msg.chat.id.0, group_id, description)
+        .await
+    {
+        Ok(Some(group)) if group.description.is_some() => {
+            format!("✅ Description of '{}' updated.", group.name)
+        }
+        Ok(Some(group)) => format!("✅ Description of '{}' removed.", group.name),
+        Ok(None) => "❌ Group not found.".to_string(),
+        Err(e) => format!("❌ Failed to update group: {}", e),
+    };
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+async fn check_group(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let [group_id] = args.words()[..] else {
+        bot.send_message(
+            msg.chat.id,
+            "Please provide a group ID (use /checkgroup <group_id>)",
+        )
+        .await?;
+        return Ok(());
+    };
+    let Some(group_id) = parse_id(&bot, msg.chat.id, group_id, "group").await? else {
+        return Ok(());
+    };
+
+    let Some((group, servers)) = find_group(&bot, &server_father, msg.chat.id, group_id).await?
+    else {
+        return Ok(());
+    };
+
+    if servers.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            format!("Group '{}' has no servers.", group.name),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut status_message = format!("📊 *Group: {}*\n\n", escape_markdown(&group.name));
+    let total_servers = servers.len();
+    let outcomes = server_father.check_servers(&servers).await;
+    let total_up = outcomes.iter().flatten().filter(|o| o.is_up).count();
+
+    for (server, outcome) in servers.iter().zip(&outcomes) {
+        status_message.push_str(&format!(
+            "{} *{}*\n`{}`\n\n",
+            status_emoji(outcome.as_ref()),
+            escape_markdown(&server.name),
+            escape_markdown(&server.target())
+        ));
+    }
+
+    // Add summary
+    status_message.push_str(&format!(
+        "Summary: {} of {} servers online",
+        total_up, total_servers
+    ));
+
+    bot.send_message(msg.chat.id, status_message)
+        .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+        .await?;
+
+    Ok(())
+}
+
+async fn uptime(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let words = args.words();
+    let (group_id, server_id, range) = match words.as_slice() {
+        ["group", id, range @ ..] => (id.parse::<i32>().ok(), None, range),
+        [id, range @ ..] => (None, id.parse::<i32>().ok(), range),
+        [] => (None, None, &[][..]),
+    };
+    let now = Utc::now().naive_utc();
+    let (from, to, period) = match parse_report_range(range, now) {
+        Ok(range) => range,
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ {}", e)).await?;
+            return Ok(());
+        }
+    };
+    let checks = server_father.check_service();
+    let default_interval = server_father.longest_check_interval(msg.chat.id).await?;
+
+    let message = match (group_id, server_id) {
+        (Some(group_id), _) => {
+            let Some((group, servers)) =
+                find_group(&bot, &server_father, msg.chat.id, group_id).await?
+            else {
+                return Ok(());
+            };
+
+            let mut total = UptimeReport::default();
+            let mut lines = String::new();
+            for server in &servers {
+                let interval = server_father.check_interval(server, default_interval);
+                let report = checks.uptime(server.id, interval, from, to).await?;
+                total.merge(&report);
+                lines.push_str(&format!(
+                    "• {}: {}, {} incident(s)\n",
+                    server.name,
+                    format_availability(&report),
+                    report.incidents
+                ));
+            }
+
+            format!(
+                "📈 Uptime of group '{}' ({})\n\n{}\n{}",
+                group.name,
+                period,
+                format_report(&total),
+                lines
+            )
+        }
+        (None, Some(server_id)) => {
+            let Some(server) = server_father
+                .server_service()
+                .get_server(msg.chat.id.0, server_id)
+                .await?
+            else {
+                bot.send_message(msg.chat.id, "❌ Server not found.")
+                    .await?;
+                return Ok(());
+            };
+
+            let interval = server_father.check_interval(&server, default_interval);
+            let report = checks.uptime(server.id, interval, from, to).await?;
+            format!(
+                "📈 Uptime of '{}' ({})\n\n{}",
+                server.name,
+                period,
+                format_report(&report)
+            )
+        }
+        (None, None) => {
+            bot.send_message(
+                msg.chat.id,
+                "Usage: /uptime <server_id> [range] or /uptime group <group_id> [range]\n\
+                 Range is 24h (default), 7d, 30d or dates like 2025-03-01..2025-03-31",
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+/// Reads an uptime period: nothing for the last 24 hours, a duration like `7d`
+/// for the time up to now, or dates as `2025-03-01..2025-03-31`, both inclusive.
+/// Returns the start, the end and a description of the period.
+fn parse_report_range(
+    args: &[&str],
+    now: NaiveDateTime,
+) -> std::result::Result<(NaiveDateTime, NaiveDateTime, String), String> {
+    let invalid = || "Invalid range. Use 24h, 7d, 30d or 2025-03-01..2025-03-31".to_string();
+
+    let arg = match args {
+        [] => "24h".to_string(),
+        [arg] => arg.to_string(),
+        [from, to] => format!("{}..{}", from, to),
+        _ => return Err(invalid()),
+    };
+
+    if let Some((from, to)) = arg.split_once("..") {
+        let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        let (Some(from), Some(to)) = (parse(from), parse(to)) else {
+            return Err(invalid());
+        };
+        if from > to {
+            return Err("The range ends before it starts.".to_string());
+        }
+        let end = to.succ_opt().unwrap_or(to).and_time(Default::default());
+        return Ok((
+            from.and_time(Default::default()),
+            end.min(now),
+            format!("{} to {}", from, to),
+        ));
+    }
+
+    let duration = parse_duration(&arg)
+        .and_then(|duration| chrono::Duration::from_std(duration).ok())
+        .filter(|duration| *duration > chrono::Duration::zero())
+        .ok_or_else(invalid)?;
+    let from = now.checked_sub_signed(duration).ok_or_else(invalid)?;
+    Ok((from, now, format!("last {}", arg)))
+}
+
+fn format_report(report: &UptimeReport) -> String {
+    let mttr = report
+        .mttr()
+        .map(format_span)
+        .unwrap_or_else(|| "n/a".into());
+    format!(
+        "Availability: {}\nDowntime: {}\nIncidents: {}\nMean time to recovery: {}\n",
+        format_availability(report),
+        format_span(report.downtime),
+        report.incidents,
+        mttr
+    )
+}
+
+fn format_availability(report: &UptimeReport) -> String {
+    report
+        .availability()
+        .map(|availability| format!("{:.3}%", availability))
+        .unwrap_or_else(|| "no data".into())
+}
+
+/// Looks up a group of the chat along with its servers. Tells the user when the
+/// group does not exist or cannot be fetched and returns `None` then.
+async fn find_group(
+    bot: &Bot,
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    group_id: i32,
+) -> Result<Option<(ServerGroupModel, Vec<ServerModel>)>> {
+    match server_father
+        .group_service()
+        .get_group(chat_id.0, group_id)
+        .await
+    {
+        Ok(Some(group)) => {
+            let servers = server_father
+                .server_service()
+                .list_servers_by_group(chat_id.0, group_id)
+                .await?;
+            Ok(Some((group, servers)))
+        }
+        Ok(None) => {
+            bot.send_message(chat_id, "❌ Group not found.").await?;
+            Ok(None)
+        }
+        Err(e) => {
+            bot.send_message(chat_id, format!("❌ Failed to fetch group: {}", e))
+                .await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Options `/editserver` takes after the server ID.
+const EDIT_SERVER_OPTIONS: &[&str] = &[
+    "name",
+    "host",
+    "port",
+    "type",
+    "http",
+    "interval",
+    "timeout",
+    "retries",
+    "failures",
+    "recoveries",
+    "family",
+    "addresses",
+];
+
+async fn edit_server(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let (server_id, reset_settings) = match args.values()[..] {
+        [server_id] => (server_id, false),
+        [server_id, reset] if reset.eq_ignore_ascii_case("default") => (server_id, true),
+        _ => {
+            bot.send_message(
+                msg.chat.id,
+                "Please provide a server ID (use /editserver <server_id> to edit it step by step, \
+                 or /editserver <server_id> name=web2 port=8443 interval=60 timeout=5 to change \
+                 fields right away, default resets the check settings)",
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    let Some(server_id) = parse_id(&bot, msg.chat.id, server_id, "server").await? else {
+        return Ok(());
+    };
+
+    let server = match server_father
+        .server_service()
+        .get_server(msg.chat.id.0, server_id)
+        .await
+    {
+        Ok(Some(server)) => server,
+        Ok(None) => {
+            bot.send_message(msg.chat.id, "❌ Server not found.")
+                .await?;
+            return Ok(());
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ Failed to fetch server: {}", e))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if args.options().is_empty() && !reset_settings {
+        let draft = NewServer::from_server(&server);
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "✏️ Editing '{}'. Send a new value for each field, or - to keep the current one.",
+                server.name
+            ),
+        )
+        .await?;
+        return ask_edit_field(
+            bot,
+            dialogue,
+            msg.chat.id,
+            server_id,
+            EditField::Name,
+            draft,
+        )
+        .await;
+    }
+
+    let mut draft = NewServer::from_server(&server);
+    if reset_settings {
+        draft.settings = CheckSettings::default();
+    }
+    let parsed = args
+        .expect_options(EDIT_SERVER_OPTIONS)
+        .and_then(|_| apply_server_options(&mut draft, &args));
+    if let Err(e) = parsed {
+        bot.send_message(msg.chat.id, format!("❌ {}.", e)).await?;
+        return Ok(());
+    }
+
+    if let Some(duplicate) = duplicate_address(
+        &server_father,
+        msg.chat.id,
+        &draft.host,
+        draft.port,
+        Some(server_id),
+    )
+    .await?
+    {
+        bot.send_message(msg.chat.id, format!("❌ {}.", duplicate))
+            .await?;
+        return Ok(());
+    }
+
+    let note = match args.option("host") {
+        Some(_) => describe_host(&draft.host).await,
+        None => String::new(),
+    };
+    let message = match server_father
+        .update_server(msg.chat.id, server_id, draft)
+        .await
+    {
+        Ok(Some(server)) => format!(
+            "✅ Server '{}' ({}) updated.\nSettings: {}",
+            server.name,
+            server.target(),
+            CheckSettings::from_server(&server)
+        ),
+        Ok(None) => "❌ Server not found.".to_string(),
+        Err(e) => format!("❌ Failed to update server: {}", e),
+    };
+    bot.send_message(msg.chat.id, format!("{}\n{}", message, note.trim_end()))
+        .await?;
+
+    Ok(())
+}
+
+/// Answer that keeps the current value of a field.
+const KEEP_VALUE: &str = "-";
+
+async fn ask_edit_field(
+    bot: Bot,
+    dialogue: MyDialogue,
+    chat_id: ChatId,
+    server_id: i32,
+    field: EditField,
+    draft: NewServer,
+) -> Result<()> {
+    let prompt = match field {
+        EditField::Name => format!("Name (current: {}):", draft.name),
+        EditField::Host => format!("Host, IP or domain (current: {}):", draft.host),
+        EditField::Port => format!("Port (current: {}):", draft.port),
+        EditField::CheckKind => format!(
+            "Check type, tcp, http, https or tls (current: {}):",
+            draft.check_kind.to_value()
+        ),
+        EditField::HttpOptions => format!(
+            "HTTP check options as METHOD PATH STATUS REDIRECTS (current: {}):",
+            draft.http_check.clone().unwrap_or_default()
+        ),
+        EditField::Settings => format!(
+            "Check settings as key=value pairs, \"default\" for the global ones (current: {}):",
+            draft.settings
+        ),
+    };
+
+    dialogue
+        .update(State::EditingServer {
+            server_id,
+            field,
+            draft,
+        })
+        .await?;
+    bot.send_message(chat_id, prompt).await?;
+    Ok(())
+}
+
+async fn receive_server_edit(
+    bot: Bot,
+    dialogue: MyDialogue,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    (server_id, field, mut draft): (i32, EditField, NewServer),
+) -> Result<()> {
+    let text = msg.text().unwrap_or_default().trim();
+    let keep = text == KEEP_VALUE;
+
+    let invalid = match field {
+        _ if keep => None,
+        EditField::Name if text.is_empty() => Some("The name cannot be empty.".to_string()),
+        EditField::Name => {
+            draft.name = text.to_string();
+            None
+        }
+        EditField::Host => match text.parse::<HostInput>() {
+            Ok(input) => {
+                draft.host = input.host;
+                draft.port = input.port.map_or(draft.port, i32::from);
+                None
+            }
+            Err(e) => Some(format!("{}.", e)),
+        },
+        EditField::Port => match parse_port(text) {
+            Ok(port) => {
+                draft.port = port;
+                None
+            }
+            Err(e) => Some(format!("{}.", e)),
+        },
+        EditField::CheckKind => match parse_check_kind(text) {
+            Some(kind) => {
+                draft.check_kind = kind;
+                None
+            }
+            None => Some("Invalid check type.".to_string()),
+        },
+        EditField::HttpOptions => match HttpCheck::parse_options(text) {
+            Ok(http_check) => {
+                draft.http_check = Some(http_check);
+                None
+            }
+            Err(e) => Some(e),
+        },
+        EditField::Settings => match draft.settings.clone().apply(text) {
+            Ok(settings) => {
+                draft.settings = settings;
+                None
+            }
+            Err(e) => Some(e),
+        },
+    };
+    if let Some(invalid) = invalid {
+        bot.send_message(msg.chat.id, format!("❌ {} Please try again:", invalid))
+            .await?;
+        return Ok(());
+    }
+
+    let is_http = matches!(draft.check_kind, CheckKind::Http | CheckKind::Https);
+    let next = match field {
+        EditField::Name => Some(EditField::Host),
+        EditField::Host => Some(EditField::Port),
+        EditField::Port => Some(EditField::CheckKind),
+        EditField::CheckKind if is_http => Some(EditField::HttpOptions),
+        EditField::CheckKind | EditField::HttpOptions => Some(EditField::Settings),
+        EditField::Settings => None,
+    };
+    if field == EditField::Host && !keep {
+        let note = describe_host(&draft.host).await;
+        if !note.is_empty() {
+            bot.send_message(msg.chat.id, note).await?;
+        }
+    }
+    if let Some(next) = next {
+        return ask_edit_field(bot, dialogue, msg.chat.id, server_id, next, draft).await;
+    }
+
+    if let Some(duplicate) = duplicate_address(
+        &server_father,
+        msg.chat.id,
+        &draft.host,
+        draft.port,
+        Some(server_id),
+    )
+    .await?
+    {
+        bot.send_message(
+            msg.chat.id,
+            format!("❌ {}, nothing was changed.", duplicate),
+        )
+        .await?;
+        dialogue.update(State::Start).await?;
+        return Ok(());
+    }
+
+    if !is_http {
+        draft.http_check = None;
+    }
+    match server_father
+        .update_server(msg.chat.id, server_id, draft)
+        .await
+    {
+        Ok(Some(server)) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Server '{}' ({}) updated.\nSettings: {}",
+                    server.name,
+                    server.target(),
+                    CheckSettings::from_server(&server)
+                ),
+            )
+            .await?;
+        }
+        Ok(None) => {
+            bot.send_message(msg.chat.id, "❌ Server not found.")
+                .await?;
+        }
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ Failed to update server: {}", e))
+                .await?;
+        }
+    }
+
+    dialogue.update(State::Start).await?;
+    Ok(())
+}
+
+/// Shortest and longest interval /setinterval accepts, in seconds.
+const INTERVAL_RANGE: std::ops::RangeInclusive<u64> = 10..=86_400;
+
+async fn set_interval(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    role: Role,
+    args: Args,
+) -> Result<()> {
+    let (chat_id, value) = match args.words()[..] {
+        [] => return show_interval(bot, server_father, msg.chat.id).await,
+        ["global", value] => (None, value),
+        [value] => (Some(msg.chat.id.0), value),
+        _ => {
+            bot.send_message(
+                msg.chat.id,
+                "Usage: /setinterval [global] <interval|default>, e.g. /setinterval 90, \
+                 /setinterval 5m or /setinterval global 1h",
+            )
+            .await?;
+            return Ok(());
+        }
+    };
+    if chat_id.is_none() && role < Role::Admin {
+        bot.send_message(
+            msg.chat.id,
+            "⛔ Only admins can change the global interval.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let scope = if chat_id.is_some() {
+        "this chat"
+    } else {
+        "all chats"
+    };
+    let settings = server_father.settings_service();
+
+    if value.eq_ignore_ascii_case("default") {
+        settings.clear_interval(chat_id).await?;
+        bot.send_message(
+            msg.chat.id,
+            format!("✅ Check interval for {} reset to the default.", scope),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let interval = match parse_interval(value) {
+        Ok(interval) => interval,
         Err(e) => {
-            bot.send_message(msg.chat.id, format!("❌ Failed to fetch groups: {}", e))
+            bot.send_message(msg.chat.id, format!("❌ {}", e)).await?;
+            return Ok(());
+        }
+    };
+
+    settings
+        .set_interval(chat_id, interval.as_secs() as i32)
+        .await?;
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "✅ Check interval for {} set to {} seconds. Running monitors pick it up right away.",
+            scope,
+            interval.as_secs()
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn show_interval(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    chat_id: ChatId,
+) -> Result<()> {
+    let settings = server_father.settings_service();
+    let show = |secs: Option<i32>| {
+        secs.map(|secs| format!("{} seconds", secs))
+            .unwrap_or_else(|| "not set".into())
+    };
+
+    bot.send_message(
+        chat_id,
+        format!(
+            "⏱ Check interval\nThis chat: {}\nGlobal: {}\nDefault: {} seconds\n\n\
+             Servers with their own interval (see /editserver) keep it.",
+            show(settings.get_interval(Some(chat_id.0)).await?),
+            show(settings.get_interval(None).await?),
+            server_father.config().check_interval
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Parses a check interval, which must lie within `INTERVAL_RANGE`.
+fn parse_interval(input: &str) -> std::result::Result<Duration, String> {
+    parse_duration(input)
+        .filter(|interval| INTERVAL_RANGE.contains(&interval.as_secs()))
+        .ok_or_else(|| {
+            format!(
+                "Invalid interval. Use seconds or a suffix like 90s, 5m or 1h, between {} and {} seconds.",
+                INTERVAL_RANGE.start(),
+                INTERVAL_RANGE.end()
+            )
+        })
+}
+
+/// Parses durations like `90`, `90s`, `5m`, `1h` or `7d`; plain numbers are seconds.
+fn parse_duration(input: &str) -> Option<Duration> {
+    let input = input.trim().to_ascii_lowercase();
+    let (value, unit) = match input.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => input.split_at(index),
+        None => (input.as_str(), "s"),
+    };
+
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+
+    value
+        .parse::<u64>()
+        .ok()
+        .and_then(|value| value.checked_mul(multiplier))
+        .map(Duration::from_secs)
+}
+
+async fn set_webhook(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let (group_id, webhook) = match args.words()[..] {
+        [group_id] => (group_id.parse::<i32>().ok(), None),
+        [group_id, "off"] => (group_id.parse::<i32>().ok(), Some(None)),
+        [group_id, url] => (group_id.parse::<i32>().ok(), Some(Some((url, None)))),
+        [group_id, url, secret] => (
+            group_id.parse::<i32>().ok(),
+            Some(Some((url, Some(secret)))),
+        ),
+        _ => (None, None),
+    };
+    let Some(group_id) = group_id else {
+        bot.send_message(
+            msg.chat.id,
+            "Usage: /setwebhook <group_id> <url> [secret] or /setwebhook <group_id> off\n\
+             With a secret, requests carry an X-Signature-256 header with the HMAC-SHA256 \
+             of the body.",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let Some(webhook) = webhook else {
+        let Some((group, _)) = find_group(&bot, &server_father, msg.chat.id, group_id).await?
+        else {
+            return Ok(());
+        };
+        let message = match &group.webhook_url {
+            Some(url) => format!(
+                "🔗 Webhook of '{}': {} ({})",
+                group.name,
+                url,
+                if group.webhook_secret.is_some() {
+                    "signed"
+                } else {
+                    "unsigned"
+                }
+            ),
+            None => format!("Group '{}' has no webhook.", group.name),
+        };
+        bot.send_message(msg.chat.id, message).await?;
+        return Ok(());
+    };
+
+    if let Some((url, _)) = webhook {
+        let valid =
+            reqwest::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https"));
+        if !valid {
+            bot.send_message(
+                msg.chat.id,
+                "❌ Invalid URL. Please use an http:// or https:// address.",
+            )
+            .await?;
+            return Ok(());
+        }
+    }
+
+    let webhook = webhook.map(|(url, secret)| (url.to_string(), secret.map(str::to_string)));
+    let Some(group) = server_father
+        .group_service()
+        .set_webhook(msg.chat.id.0, group_id, webhook)
+        .await?
+    else {
+        bot.send_message(msg.chat.id, "❌ Group not found.").await?;
+        return Ok(());
+    };
+
+    let Some(url) = group.webhook_url.clone() else {
+        bot.send_message(
+            msg.chat.id,
+            format!("✅ Webhook of '{}' removed.", group.name),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    // One attempt only, the user is waiting for the answer
+    let config = server_father.config();
+    let test = match WebhookNotifier::new(
+        url,
+        group.webhook_secret.clone(),
+        0,
+        Duration::from_secs(config.webhook_timeout),
+    ) {
+        Ok(webhook) => webhook.notify(&StatusEvent::test(&group)).await,
+        Err(e) => Err(e),
+    };
+    let result = match test {
+        Ok(()) => "a test event was delivered".to_string(),
+        Err(e) => format!("but the test event failed: {}", e),
+    };
+
+    bot.send_message(
+        msg.chat.id,
+        format!("✅ Webhook of '{}' set, {}.", group.name, result),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn set_email(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let args = args.words();
+    let Some(group_id) = args
+        .first()
+        .and_then(|group_id| group_id.parse::<i32>().ok())
+    else {
+        bot.send_message(
+            msg.chat.id,
+            "Usage: /setemail <group_id> <address>[,<address>...] or /setemail <group_id> off",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let recipients = match &args[1..] {
+        [] => {
+            let Some((group, _)) = find_group(&bot, &server_father, msg.chat.id, group_id).await?
+            else {
+                return Ok(());
+            };
+            let recipients = group.email_recipients();
+            let message = if recipients.is_empty() {
+                format!("Group '{}' has no mail recipients.", group.name)
+            } else {
+                format!(
+                    "📧 Mail recipients of '{}': {}",
+                    group.name,
+                    recipients.join(", ")
+                )
+            };
+            bot.send_message(msg.chat.id, message).await?;
+            return Ok(());
+        }
+        ["off"] => Vec::new(),
+        addresses => addresses
+            .iter()
+            .flat_map(|addresses| addresses.split(','))
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(String::from)
+            .collect(),
+    };
+
+    for address in &recipients {
+        if let Err(e) = email::validate_address(address) {
+            bot.send_message(
+                msg.chat.id,
+                format!("❌ Invalid address '{}': {}", address, e),
+            )
+            .await?;
+            return Ok(());
+        }
+    }
+
+    let mailer = server_father.mailer();
+    if mailer.is_none() && !recipients.is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "❌ Mail is not configured. Set SMTP_HOST and SMTP_FROM first.",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let Some(group) = server_father
+        .group_service()
+        .set_email_recipients(msg.chat.id.0, group_id, &recipients)
+        .await?
+    else {
+        bot.send_message(msg.chat.id, "❌ Group not found.").await?;
+        return Ok(());
+    };
+
+    let Some(mailer) = mailer.filter(|_| !recipients.is_empty()) else {
+        bot.send_message(
+            msg.chat.id,
+            format!("✅ Mail alerts of '{}' turned off.", group.name),
+        )
+        .await?;
+        return Ok(());
+    };
+
+    // Sent right away instead of batched, the user is waiting for the answer
+    let result = match mailer.send(&recipients, &[StatusEvent::test(&group)]).await {
+        Ok(()) => "a test mail was sent".to_string(),
+        Err(e) => format!("but the test mail failed: {}", e),
+    };
+
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "✅ Mail recipients of '{}' set to {}, {}.",
+            group.name,
+            recipients.join(", "),
+            result
+        ),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn ack_incident(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let Some(arg) = args.words().first().copied() else {
+        return list_incidents(bot, server_father, msg.chat.id).await;
+    };
+
+    let message = match arg.trim_start_matches('#').parse::<i32>() {
+        Ok(incident_id) => {
+            acknowledge_incident(&server_father, msg.chat.id, incident_id, msg.from.as_ref())
+                .await?
+                .1
+        }
+        Err(_) => "Invalid incident ID. Please enter a number.".to_string(),
+    };
+
+    bot.send_message(msg.chat.id, message).await?;
+    Ok(())
+}
+
+/// Acknowledges an incident of the chat on behalf of `user`. Returns whether it
+/// was acknowledged just now and the message to show either way.
+async fn acknowledge_incident(
+    server_father: &ServerFatherBot,
+    chat_id: ChatId,
+    incident_id: i32,
+    user: Option<&User>,
+) -> Result<(bool, String)> {
+    let incidents = server_father.incident_service();
+
+    let message = match incidents.get_incident(chat_id.0, incident_id).await? {
+        None => "❌ Incident not found.".to_string(),
+        Some(incident) if incident.resolved_at.is_some() => {
+            format!("ℹ️ Incident #{} is already resolved.", incident.id)
+        }
+        Some(incident) if incident.acknowledged_at.is_some() => {
+            format!("ℹ️ Incident #{} was already acknowledged.", incident.id)
+        }
+        Some(incident) => {
+            incidents
+                .acknowledge(chat_id.0, incident.id, user.map(|user| user.id.0 as i64))
                 .await?;
+            return Ok((
+                true,
+                format!(
+                    "✅ Incident #{} acknowledged by {}, reminders are silenced.",
+                    incident.id,
+                    user.map_or_else(|| "an anonymous admin".to_string(), User::full_name)
+                ),
+            ));
+        }
+    };
+
+    Ok((false, message))
+}
+
+async fn list_incidents(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    chat_id: ChatId,
+) -> Result<()> {
+    let incidents = server_father
+        .incident_service()
+        .list_open(chat_id.0)
+        .await?;
+
+    if incidents.is_empty() {
+        bot.send_message(chat_id, "✅ No open incidents.").await?;
+        return Ok(());
+    }
+
+    let now = Utc::now().naive_utc();
+    let mut message = String::from("🚨 Open incidents\n\n");
+    for incident in incidents {
+        let server = server_father
+            .server_service()
+            .get_server(chat_id.0, incident.server_id)
+            .await?;
+        message.push_str(&format!(
+            "#{} {}: down for {}{}\n",
+            incident.id,
+            server.map_or_else(
+                || format!("server {}", incident.server_id),
+                |server| { format!("'{}'", server.name) }
+            ),
+            format_span(incident.duration(now)),
+            if incident.acknowledged_at.is_some() {
+                ", acknowledged"
+            } else {
+                ""
+            }
+        ));
+    }
+    message.push_str("\nUse /ack <incident_id> to silence reminders.");
+
+    bot.send_message(chat_id, message).await?;
+    Ok(())
+}
+
+async fn grant_role(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    // Replying to someone's message saves looking up their user ID
+    let (user_id, role) = match (&args.words()[..], replied_user_id(&msg)) {
+        ([], _) => return list_roles(bot, server_father, msg.chat.id).await,
+        ([role], Some(user_id)) => (Some(user_id), Some(*role)),
+        ([user_id, role], _) => (user_id.parse::<i64>().ok(), Some(*role)),
+        _ => (None, None),
+    };
+    let (Some(user_id), Some(role)) = (user_id, role) else {
+        bot.send_message(
+            msg.chat.id,
+            "Usage: /grant <user_id> <admin|operator|viewer>, or reply to a message \
+             of the user with /grant <role>",
+        )
+        .await?;
+        return Ok(());
+    };
+    let role = match role.parse::<Role>() {
+        Ok(role) => role,
+        Err(e) => {
+            bot.send_message(msg.chat.id, format!("❌ {}", e)).await?;
+            return Ok(());
         }
+    };
+
+    let granted_by = msg.from.as_ref().map(|user| user.id.0 as i64);
+    server_father
+        .role_service()
+        .grant(user_id, role, granted_by)
+        .await?;
+    bot.send_message(msg.chat.id, format!("✅ User {} is now {}.", user_id, role))
+        .await?;
+
+    Ok(())
+}
+
+async fn revoke_role(
+    bot: Bot,
+    server_father: Arc<ServerFatherBot>,
+    msg: Message,
+    args: Args,
+) -> Result<()> {
+    let user_id = match args.words().first() {
+        Some(arg) => arg.parse::<i64>().ok(),
+        None => replied_user_id(&msg),
+    };
+    let Some(user_id) = user_id else {
+        bot.send_message(
+            msg.chat.id,
+            "Usage: /revoke <user_id>, or reply to a message of the user with /revoke",
+        )
+        .await?;
+        return Ok(());
+    };
+
+    let message = if server_father.role_service().revoke(user_id).await? {
+        format!("✅ Role of user {} revoked.", user_id)
+    } else {
+        format!("ℹ️ User {} has no granted role.", user_id)
+    };
+    let note = if server_father.config().admin_ids.contains(&user_id) {
+        "\nThey stay admin through ADMIN_IDS."
+    } else {
+        ""
+    };
+
+    bot.send_message(msg.chat.id, format!("{}{}", message, note))
+        .await?;
+    Ok(())
+}
+
+async fn list_roles(bot: Bot, server_father: Arc<ServerFatherBot>, chat_id: ChatId) -> Result<()> {
+    let roles = server_father.role_service().list_roles().await?;
+    let config = server_father.config();
+
+    let mut message = String::from("🔑 Roles\n\n");
+    for admin_id in &config.admin_ids {
+        message.push_str(&format!("{}: admin (ADMIN_IDS)\n", admin_id));
+    }
+    for user_role in &roles {
+        message.push_str(&format!("{}: {}\n", user_role.user_id, user_role.role));
     }
+    if config.admin_ids.is_empty() && roles.is_empty() {
+        message.push_str("No roles granted yet.\n");
+    }
+    message.push_str(if config.allowed_ids.is_empty() {
+        "\nEveryone else is a viewer, ALLOWED_IDS is not set."
+    } else {
+        "\nEveryone else on ALLOWED_IDS is a viewer."
+    });
 
+    bot.send_message(chat_id, message).await?;
     Ok(())
 }
 
+/// User ID of the author of the message being replied to.
+fn replied_user_id(msg: &Message) -> Option<i64> {
+    msg.reply_to_message()
+        .and_then(|reply| reply.from.as_ref())
+        .map(|user| user.id.0 as i64)
+}
+
 // Continue with other command handlers...
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buttons_answer_their_own_prompt() {
+        assert!(Action::RemoveServer(3).answers(&State::AwaitingServerId));
+        assert!(Action::PickGroup(1).answers(&State::AwaitingGroupIdForServer));
+        assert!(Action::AddToGroup {
+            group_id: 1,
+            server_id: 3
+        }
+        .answers(&State::AwaitingServerForGroup { group_id: 1 }));
+        assert!(Action::Cancel.answers(&State::AwaitingGroupIdForRemoval));
+    }
+
+    #[test]
+    fn stale_buttons_leave_wizards_alone() {
+        let wizard = State::AwaitingServerPort {
+            name: "web".to_string(),
+        };
+        assert!(!Action::Cancel.answers(&wizard));
+        assert!(!Action::RemoveServer(3).answers(&wizard));
+        assert!(!Action::PickGroup(1).answers(&State::AwaitingServerId));
+        assert!(!Action::AddToGroup {
+            group_id: 2,
+            server_id: 3
+        }
+        .answers(&State::AwaitingServerForGroup { group_id: 1 }));
+        assert!(!Action::ConfirmRemoveServer(3).answers(&State::Start));
+        assert!(!Action::Ack(1).answers(&State::AwaitingServerId));
+    }
+}
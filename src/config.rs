@@ -1,10 +1,58 @@
 use crate::error::{BotError, Result};
 use std::env;
+use std::str::FromStr;
+
+/// How the SMTP connection is secured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Upgrades a plain connection, usually on port 587
+    StartTls,
+    /// TLS from the start, usually on port 465
+    Tls,
+    /// Unencrypted, for local relays and test sinks
+    Plain,
+}
+
+impl FromStr for SmtpSecurity {
+    type Err = BotError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "starttls" => Ok(SmtpSecurity::StartTls),
+            "tls" => Ok(SmtpSecurity::Tls),
+            "plain" | "none" => Ok(SmtpSecurity::Plain),
+            _ => Err(BotError::Environment("Invalid SMTP_SECURITY".into())),
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct Config {
     pub database_url: String,
     pub check_interval: u64, // in seconds
+    pub check_timeout: u64,  // in seconds
+    pub check_retries: u32,
+    pub cert_warning_days: Vec<i64>, // days before expiry to warn at
+    pub max_concurrent_checks: usize,
+    pub check_deadline: u64,     // in seconds, bounds a whole round of checks
+    pub failure_threshold: u32,  // failed checks in a row before a server is down
+    pub recovery_threshold: u32, // successful checks in a row before a server is up
+    pub flap_threshold: usize,   // state changes within flap_window that count as flapping
+    pub flap_window: u64,        // in seconds
+    pub realert_interval: u64,   // in seconds, reminders of unacknowledged incidents
+    pub webhook_retries: u32,
+    pub webhook_timeout: u64,      // in seconds
+    pub smtp_host: Option<String>, // mail is off without it
+    pub smtp_port: u16,
+    pub smtp_security: SmtpSecurity,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_from: Option<String>,
+    pub email_batch_window: u64, // in seconds, events within it go out in one mail
+    pub dialogue_ttl: u64,       // in seconds, idle wizards are ended after it
+    pub default_owner_chat_id: Option<i64>, // receives servers and groups from before ownership
+    pub allowed_ids: Vec<i64>,   // user or chat ids, empty lets everyone in
+    pub admin_ids: Vec<i64>,     // user ids that are always admins
 }
 
 impl Config {
@@ -17,9 +65,165 @@ impl Config {
             .parse()
             .map_err(|_| BotError::Environment("Invalid CHECK_INTERVAL".into()))?;
 
+        let check_timeout = env::var("CHECK_TIMEOUT")
+            .unwrap_or_else(|_| "5".into())
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid CHECK_TIMEOUT".into()))?;
+
+        let check_retries = env::var("CHECK_RETRIES")
+            .unwrap_or_else(|_| "0".into())
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid CHECK_RETRIES".into()))?;
+
+        let cert_warning_days = env::var("CERT_WARNING_DAYS")
+            .unwrap_or_else(|_| "30,14,7,1".into())
+            .split(',')
+            .map(|days| days.trim().parse())
+            .collect::<std::result::Result<Vec<i64>, _>>()
+            .map_err(|_| BotError::Environment("Invalid CERT_WARNING_DAYS".into()))?;
+
+        let max_concurrent_checks = env::var("MAX_CONCURRENT_CHECKS")
+            .unwrap_or_else(|_| "10".into())
+            .parse()
+            .ok()
+            .filter(|&max| max > 0)
+            .ok_or_else(|| BotError::Environment("Invalid MAX_CONCURRENT_CHECKS".into()))?;
+
+        let check_deadline = env::var("CHECK_DEADLINE")
+            .unwrap_or_else(|_| "30".into())
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid CHECK_DEADLINE".into()))?;
+
+        let failure_threshold = env::var("FAILURE_THRESHOLD")
+            .unwrap_or_else(|_| "1".into())
+            .parse()
+            .ok()
+            .filter(|&threshold| threshold > 0)
+            .ok_or_else(|| BotError::Environment("Invalid FAILURE_THRESHOLD".into()))?;
+
+        let recovery_threshold = env::var("RECOVERY_THRESHOLD")
+            .unwrap_or_else(|_| "1".into())
+            .parse()
+            .ok()
+            .filter(|&threshold| threshold > 0)
+            .ok_or_else(|| BotError::Environment("Invalid RECOVERY_THRESHOLD".into()))?;
+
+        let flap_threshold = env::var("FLAP_THRESHOLD")
+            .unwrap_or_else(|_| "5".into()) // 0 turns flap detection off
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid FLAP_THRESHOLD".into()))?;
+
+        let flap_window = env::var("FLAP_WINDOW")
+            .unwrap_or_else(|_| "900".into()) // default 15 minutes
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid FLAP_WINDOW".into()))?;
+
+        let realert_interval = env::var("REALERT_INTERVAL")
+            .unwrap_or_else(|_| "3600".into()) // 0 turns reminders off
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid REALERT_INTERVAL".into()))?;
+
+        let webhook_retries = env::var("WEBHOOK_RETRIES")
+            .unwrap_or_else(|_| "3".into())
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid WEBHOOK_RETRIES".into()))?;
+
+        let webhook_timeout = env::var("WEBHOOK_TIMEOUT")
+            .unwrap_or_else(|_| "10".into())
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid WEBHOOK_TIMEOUT".into()))?;
+
+        let smtp_host = optional_var("SMTP_HOST");
+        let smtp_security: SmtpSecurity = env::var("SMTP_SECURITY")
+            .unwrap_or_else(|_| "starttls".into())
+            .parse()?;
+
+        let smtp_port = match optional_var("SMTP_PORT") {
+            Some(port) => port
+                .parse()
+                .map_err(|_| BotError::Environment("Invalid SMTP_PORT".into()))?,
+            None => match smtp_security {
+                SmtpSecurity::StartTls => 587,
+                SmtpSecurity::Tls => 465,
+                SmtpSecurity::Plain => 25,
+            },
+        };
+
+        let smtp_username = optional_var("SMTP_USERNAME");
+        let smtp_password = optional_var("SMTP_PASSWORD");
+        let smtp_from = optional_var("SMTP_FROM");
+        if smtp_host.is_some() && smtp_from.is_none() {
+            return Err(BotError::Environment(
+                "SMTP_FROM must be set along with SMTP_HOST".into(),
+            ));
+        }
+
+        let email_batch_window = env::var("EMAIL_BATCH_WINDOW")
+            .unwrap_or_else(|_| "30".into())
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid EMAIL_BATCH_WINDOW".into()))?;
+
+        let dialogue_ttl = env::var("DIALOGUE_TTL")
+            .unwrap_or_else(|_| "900".into()) // 0 keeps dialogues forever
+            .parse()
+            .map_err(|_| BotError::Environment("Invalid DIALOGUE_TTL".into()))?;
+
+        let default_owner_chat_id = env::var("DEFAULT_OWNER_CHAT_ID")
+            .ok()
+            .filter(|chat_id| !chat_id.trim().is_empty())
+            .map(|chat_id| chat_id.trim().parse())
+            .transpose()
+            .map_err(|_| BotError::Environment("Invalid DEFAULT_OWNER_CHAT_ID".into()))?;
+
+        let allowed_ids = parse_ids("ALLOWED_IDS")?;
+        let admin_ids = parse_ids("ADMIN_IDS")?;
+
         Ok(Config {
             database_url,
             check_interval,
+            check_timeout,
+            check_retries,
+            cert_warning_days,
+            max_concurrent_checks,
+            check_deadline,
+            failure_threshold,
+            recovery_threshold,
+            flap_threshold,
+            flap_window,
+            realert_interval,
+            webhook_retries,
+            webhook_timeout,
+            smtp_host,
+            smtp_port,
+            smtp_security,
+            smtp_username,
+            smtp_password,
+            smtp_from,
+            email_batch_window,
+            dialogue_ttl,
+            default_owner_chat_id,
+            allowed_ids,
+            admin_ids,
         })
     }
 }
+
+/// Reads a variable that may be unset or left empty, e.g. by docker-compose.
+fn optional_var(var: &str) -> Option<String> {
+    env::var(var)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Reads a comma separated list of Telegram ids, unset or empty is no ids.
+fn parse_ids(var: &str) -> Result<Vec<i64>> {
+    env::var(var)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| id.parse())
+        .collect::<std::result::Result<Vec<i64>, _>>()
+        .map_err(|_| BotError::Environment(format!("Invalid {}", var)))
+}
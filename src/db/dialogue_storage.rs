@@ -0,0 +1,143 @@
+use super::entities::{dialogue, prelude::*};
+use crate::error::BotError;
+use chrono::Utc;
+use futures::future::BoxFuture;
+use sea_orm::sea_query::OnConflict;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::dispatching::dialogue::Storage;
+use teloxide::types::ChatId;
+
+/// Keeps dialogues in the `dialogues` table, so wizards survive a restart.
+/// Dialogues untouched for longer than the TTL are dropped when read.
+pub struct DialogueStorage<D> {
+    db: DatabaseConnection,
+    ttl: Option<Duration>,
+    state: PhantomData<fn() -> D>,
+}
+
+impl<D> DialogueStorage<D> {
+    /// `ttl` of `None` keeps dialogues until they finish.
+    pub fn new(db: DatabaseConnection, ttl: Option<Duration>) -> Arc<Self> {
+        Arc::new(Self {
+            db,
+            ttl,
+            state: PhantomData,
+        })
+    }
+
+    /// Deletes every expired dialogue and hands back the readable ones, so the
+    /// chats can be told their wizard ended.
+    pub async fn take_expired(&self) -> Result<Vec<(ChatId, D)>, BotError>
+    where
+        D: DeserializeOwned,
+    {
+        let Some(cutoff) = self.cutoff() else {
+            return Ok(Vec::new());
+        };
+
+        let expired = Dialogue::find()
+            .filter(dialogue::Column::UpdatedAt.lt(cutoff))
+            .all(&self.db)
+            .await?;
+        if expired.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let chat_ids = expired.iter().map(|dialogue| dialogue.chat_id);
+        Dialogue::delete_many()
+            .filter(dialogue::Column::ChatId.is_in(chat_ids))
+            .filter(dialogue::Column::UpdatedAt.lt(cutoff))
+            .exec(&self.db)
+            .await?;
+
+        Ok(expired
+            .into_iter()
+            .filter_map(|dialogue| {
+                let state = serde_json::from_str(&dialogue.state).ok()?;
+                Some((ChatId(dialogue.chat_id), state))
+            })
+            .collect())
+    }
+
+    /// Oldest update time a dialogue may have to still count.
+    fn cutoff(&self) -> Option<chrono::NaiveDateTime> {
+        let ttl = chrono::Duration::from_std(self.ttl?).ok()?;
+        Some(Utc::now().naive_utc() - ttl)
+    }
+}
+
+impl<D> Storage<D> for DialogueStorage<D>
+where
+    D: Serialize + DeserializeOwned + Send + 'static,
+{
+    type Error = BotError;
+
+    fn remove_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+    ) -> BoxFuture<'static, Result<(), Self::Error>> {
+        Box::pin(async move {
+            Dialogue::delete_by_id(chat_id.0).exec(&self.db).await?;
+            Ok(())
+        })
+    }
+
+    fn update_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+        state: D,
+    ) -> BoxFuture<'static, Result<(), Self::Error>> {
+        Box::pin(async move {
+            let state = serde_json::to_string(&state)
+                .map_err(|e| BotError::Dialogue(format!("Failed to store state: {}", e)))?;
+
+            let dialogue = dialogue::ActiveModel {
+                chat_id: Set(chat_id.0),
+                state: Set(state),
+                updated_at: Set(Utc::now().naive_utc()),
+            };
+            Dialogue::insert(dialogue)
+                .on_conflict(
+                    OnConflict::column(dialogue::Column::ChatId)
+                        .update_columns([dialogue::Column::State, dialogue::Column::UpdatedAt])
+                        .to_owned(),
+                )
+                .exec_without_returning(&self.db)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn get_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+    ) -> BoxFuture<'static, Result<Option<D>, Self::Error>> {
+        Box::pin(async move {
+            let Some(dialogue) = Dialogue::find_by_id(chat_id.0).one(&self.db).await? else {
+                return Ok(None);
+            };
+
+            let expired = self
+                .cutoff()
+                .is_some_and(|cutoff| dialogue.updated_at < cutoff);
+            if expired {
+                Dialogue::delete_by_id(chat_id.0).exec(&self.db).await?;
+                return Ok(None);
+            }
+
+            // A state from an older version that no longer parses starts over
+            match serde_json::from_str(&dialogue.state) {
+                Ok(state) => Ok(Some(state)),
+                Err(e) => {
+                    tracing::warn!("Dropping unreadable dialogue of chat {}: {}", chat_id, e);
+                    Dialogue::delete_by_id(chat_id.0).exec(&self.db).await?;
+                    Ok(None)
+                }
+            }
+        })
+    }
+}
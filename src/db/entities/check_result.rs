@@ -0,0 +1,67 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "check_results")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub server_id: i32,
+    pub checked_at: DateTime,
+    pub is_up: bool,
+    pub latency_ms: Option<i32>,
+    pub error_kind: Option<ErrorKind>,
+    pub incident_id: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+pub enum ErrorKind {
+    #[sea_orm(string_value = "timeout")]
+    Timeout,
+    #[sea_orm(string_value = "refused")]
+    Refused,
+    #[sea_orm(string_value = "unreachable")]
+    Unreachable,
+    #[sea_orm(string_value = "resolve")]
+    Resolve,
+    #[sea_orm(string_value = "tls")]
+    Tls,
+    #[sea_orm(string_value = "status")]
+    Status,
+    /// Some of the addresses of the server failed
+    #[sea_orm(string_value = "partial")]
+    Partial,
+    #[sea_orm(string_value = "other")]
+    Other,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::server::Entity",
+        from = "Column::ServerId",
+        to = "super::server::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Server,
+    #[sea_orm(
+        belongs_to = "super::incident::Entity",
+        from = "Column::IncidentId",
+        to = "super::incident::Column::Id"
+    )]
+    Incident,
+}
+
+impl Related<super::server::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Server.def()
+    }
+}
+
+impl Related<super::incident::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Incident.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
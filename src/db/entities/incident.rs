@@ -0,0 +1,51 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "incidents")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub server_id: i32,
+    pub chat_id: i64,
+    pub opened_at: DateTime,
+    pub acknowledged_at: Option<DateTime>,
+    pub acknowledged_by: Option<i64>,
+    pub resolved_at: Option<DateTime>,
+    pub last_alerted_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::server::Entity",
+        from = "Column::ServerId",
+        to = "super::server::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Server,
+    #[sea_orm(has_many = "super::check_result::Entity")]
+    CheckResult,
+}
+
+impl Related<super::server::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Server.def()
+    }
+}
+
+impl Related<super::check_result::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CheckResult.def()
+    }
+}
+
+impl Model {
+    /// How long the server was down, up to now while the incident is open.
+    pub fn duration(&self, now: DateTime) -> std::time::Duration {
+        (self.resolved_at.unwrap_or(now) - self.opened_at)
+            .to_std()
+            .unwrap_or_default()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,5 +1,12 @@
 //! SeaORM Entity. Generated by sea-orm-codegen
 
+pub mod check_result;
+pub mod dialogue;
+pub mod incident;
+pub mod monitor_setting;
+pub mod monitor_subscription;
 pub mod prelude;
 pub mod server;
 pub mod server_group;
+pub mod server_group_member;
+pub mod user_role;
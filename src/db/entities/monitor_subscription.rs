@@ -0,0 +1,32 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "monitor_subscriptions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub chat_id: i64,
+    pub group_id: Option<i32>,
+    pub check_interval: Option<i32>,
+    pub paused: bool,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::server_group::Entity",
+        from = "Column::GroupId",
+        to = "super::server_group::Column::Id",
+        on_delete = "Cascade"
+    )]
+    ServerGroup,
+}
+
+impl Related<super::server_group::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ServerGroup.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
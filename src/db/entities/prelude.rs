@@ -1,3 +1,11 @@
 //! SeaORM Entity. Generated by sea-orm-codegen
 
+pub use super::check_result::Entity as CheckResult;
+pub use super::dialogue::Entity as Dialogue;
+pub use super::incident::Entity as Incident;
+pub use super::monitor_setting::Entity as MonitorSetting;
+pub use super::monitor_subscription::Entity as MonitorSubscription;
 pub use super::server::Entity as Server;
+pub use super::server_group::Entity as ServerGroup;
+pub use super::server_group_member::Entity as ServerGroupMember;
+pub use super::user_role::Entity as UserRole;
@@ -1,4 +1,6 @@
+use crate::monitor::address;
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
 #[sea_orm(table_name = "servers")]
@@ -8,26 +10,115 @@ pub struct Model {
     pub name: String,
     pub host: String,
     pub port: i32,
-    pub group_id: Option<i32>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub last_check: Option<DateTime>,
     pub is_active: bool,
+    pub last_status: Option<bool>,
+    pub check_kind: CheckKind,
+    pub http_method: Option<HttpMethod>,
+    pub http_path: Option<String>,
+    pub expected_status: Option<String>,
+    pub follow_redirects: bool,
+    pub cert_sni: Option<String>,
+    pub cert_issuer: Option<String>,
+    pub cert_not_after: Option<DateTime>,
+    pub cert_alerted_days: Option<i32>,
+    pub check_interval: Option<i32>,
+    pub check_timeout: Option<i32>,
+    pub check_retries: Option<i32>,
+    pub owner_chat_id: Option<i64>,
+    pub failure_threshold: Option<i32>,
+    pub recovery_threshold: Option<i32>,
+    pub address_family: Option<AddressFamily>,
+    pub check_all_addresses: bool,
+}
+
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(16))")]
+#[serde(rename_all = "lowercase")]
+pub enum CheckKind {
+    #[default]
+    #[sea_orm(string_value = "tcp")]
+    Tcp,
+    #[sea_orm(string_value = "http")]
+    Http,
+    #[sea_orm(string_value = "https")]
+    Https,
+    #[sea_orm(string_value = "tls")]
+    Tls,
+}
+
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(8))")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HttpMethod {
+    #[default]
+    #[sea_orm(string_value = "GET")]
+    Get,
+    #[sea_orm(string_value = "HEAD")]
+    Head,
+}
+
+/// IP version a server is checked over, both when unset.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::N(8))")]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFamily {
+    #[sea_orm(string_value = "ipv4")]
+    Ipv4,
+    #[sea_orm(string_value = "ipv6")]
+    Ipv6,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
-    #[sea_orm(
-        belongs_to = "super::server_group::Entity",
-        from = "Column::GroupId",
-        to = "super::server_group::Column::Id"
-    )]
-    ServerGroup,
+    #[sea_orm(has_many = "super::server_group_member::Entity")]
+    ServerGroupMember,
+    #[sea_orm(has_many = "super::check_result::Entity")]
+    CheckResult,
+}
+
+impl Related<super::server_group_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ServerGroupMember.def()
+    }
 }
 
 impl Related<super::server_group::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::ServerGroup.def()
+        super::server_group_member::Relation::ServerGroup.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::server_group_member::Relation::Server.def().rev())
+    }
+}
+
+impl Related<super::check_result::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::CheckResult.def()
+    }
+}
+
+impl Model {
+    /// Human readable probe target, e.g. `10.0.0.5:22` or `https://example.com:443/health`.
+    pub fn target(&self) -> String {
+        let address = address::join(&self.host, self.port as u16);
+        match self.check_kind {
+            CheckKind::Tcp => address,
+            CheckKind::Tls => format!("tls://{}", address),
+            CheckKind::Http | CheckKind::Https => format!(
+                "{}://{}{}",
+                self.check_kind.to_value(),
+                address,
+                self.http_path.as_deref().unwrap_or("/")
+            ),
+        }
     }
 }
 
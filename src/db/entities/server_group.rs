@@ -8,17 +8,50 @@ pub struct Model {
     pub name: String,
     pub created_at: DateTime,
     pub updated_at: DateTime,
+    pub owner_chat_id: Option<i64>,
+    pub webhook_url: Option<String>,
+    pub webhook_secret: Option<String>,
+    pub email_recipients: Option<String>,
+    pub description: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
-    #[sea_orm(has_many = "super::server::Entity")]
-    Server,
+    #[sea_orm(has_many = "super::server_group_member::Entity")]
+    ServerGroupMember,
+}
+
+impl Related<super::server_group_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ServerGroupMember.def()
+    }
 }
 
 impl Related<super::server::Entity> for Entity {
     fn to() -> RelationDef {
-        Relation::Server.def()
+        super::server_group_member::Relation::Server.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(
+            super::server_group_member::Relation::ServerGroup
+                .def()
+                .rev(),
+        )
+    }
+}
+
+impl Model {
+    /// Addresses alerts of the group are mailed to.
+    pub fn email_recipients(&self) -> Vec<String> {
+        self.email_recipients
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(String::from)
+            .collect()
     }
 }
 
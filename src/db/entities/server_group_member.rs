@@ -0,0 +1,44 @@
+use sea_orm::entity::prelude::*;
+
+/// Membership of a server in a group, a server can be in several groups.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "server_group_members")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub server_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub group_id: i32,
+    pub created_at: DateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::server::Entity",
+        from = "Column::ServerId",
+        to = "super::server::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Server,
+    #[sea_orm(
+        belongs_to = "super::server_group::Entity",
+        from = "Column::GroupId",
+        to = "super::server_group::Column::Id",
+        on_delete = "Cascade"
+    )]
+    ServerGroup,
+}
+
+impl Related<super::server::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Server.def()
+    }
+}
+
+impl Related<super::server_group::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ServerGroup.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
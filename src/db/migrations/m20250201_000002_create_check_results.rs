@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(CheckResults::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(CheckResults::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(CheckResults::ServerId).integer().not_null())
+                    .col(
+                        ColumnDef::new(CheckResults::CheckedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(CheckResults::IsUp).boolean().not_null())
+                    .col(ColumnDef::new(CheckResults::LatencyMs).integer())
+                    .col(ColumnDef::new(CheckResults::ErrorKind).string_len(16))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_check_result_server")
+                            .from(CheckResults::Table, CheckResults::ServerId)
+                            .to(Servers::Table, Servers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_check_results_server_checked_at")
+                    .table(CheckResults::Table)
+                    .col(CheckResults::ServerId)
+                    .col(CheckResults::CheckedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(CheckResults::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum CheckResults {
+    Table,
+    Id,
+    ServerId,
+    CheckedAt,
+    IsUp,
+    LatencyMs,
+    ErrorKind,
+}
+
+#[derive(DeriveIden)]
+enum Servers {
+    Table,
+    Id,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // SQLite only accepts a single column per ALTER TABLE statement
+        let columns = [
+            ColumnDef::new(Servers::CheckKind)
+                .string_len(16)
+                .not_null()
+                .default("tcp")
+                .to_owned(),
+            ColumnDef::new(Servers::HttpMethod).string_len(8).to_owned(),
+            ColumnDef::new(Servers::HttpPath).string().to_owned(),
+            ColumnDef::new(Servers::ExpectedStatus)
+                .string_len(16)
+                .to_owned(),
+            ColumnDef::new(Servers::FollowRedirects)
+                .boolean()
+                .not_null()
+                .default(false)
+                .to_owned(),
+        ];
+
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Servers::Table)
+                        .add_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Servers::CheckKind,
+            Servers::HttpMethod,
+            Servers::HttpPath,
+            Servers::ExpectedStatus,
+            Servers::FollowRedirects,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Servers::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Servers {
+    Table,
+    CheckKind,
+    HttpMethod,
+    HttpPath,
+    ExpectedStatus,
+    FollowRedirects,
+}
@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let columns = [
+            ColumnDef::new(Servers::CertSni).string().to_owned(),
+            ColumnDef::new(Servers::CertIssuer).string().to_owned(),
+            ColumnDef::new(Servers::CertNotAfter).timestamp().to_owned(),
+            ColumnDef::new(Servers::CertAlertedDays)
+                .integer()
+                .to_owned(),
+        ];
+
+        for column in columns {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Servers::Table)
+                        .add_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [
+            Servers::CertSni,
+            Servers::CertIssuer,
+            Servers::CertNotAfter,
+            Servers::CertAlertedDays,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Servers::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Servers {
+    Table,
+    CertSni,
+    CertIssuer,
+    CertNotAfter,
+    CertAlertedDays,
+}
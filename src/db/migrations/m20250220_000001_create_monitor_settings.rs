@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MonitorSettings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MonitorSettings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    // NULL holds the global setting
+                    .col(ColumnDef::new(MonitorSettings::ChatId).big_integer())
+                    .col(
+                        ColumnDef::new(MonitorSettings::CheckInterval)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MonitorSettings::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_monitor_settings_chat_id")
+                    .table(MonitorSettings::Table)
+                    .col(MonitorSettings::ChatId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MonitorSettings::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MonitorSettings {
+    Table,
+    Id,
+    ChatId,
+    CheckInterval,
+    UpdatedAt,
+}
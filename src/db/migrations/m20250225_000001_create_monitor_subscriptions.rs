@@ -0,0 +1,75 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MonitorSubscriptions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MonitorSubscriptions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MonitorSubscriptions::ChatId)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    // NULL watches every server
+                    .col(ColumnDef::new(MonitorSubscriptions::GroupId).integer())
+                    .col(ColumnDef::new(MonitorSubscriptions::CheckInterval).integer())
+                    .col(
+                        ColumnDef::new(MonitorSubscriptions::Paused)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(MonitorSubscriptions::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_monitor_subscription_group")
+                            .from(MonitorSubscriptions::Table, MonitorSubscriptions::GroupId)
+                            .to(ServerGroups::Table, ServerGroups::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MonitorSubscriptions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MonitorSubscriptions {
+    Table,
+    Id,
+    ChatId,
+    GroupId,
+    CheckInterval,
+    Paused,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ServerGroups {
+    Table,
+    Id,
+}
@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // NULL marks rows from before ownership, they are handed to
+        // DEFAULT_OWNER_CHAT_ID on startup and hidden from every chat until then
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Servers::Table)
+                    .add_column(ColumnDef::new(Servers::OwnerChatId).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ServerGroups::Table)
+                    .add_column(ColumnDef::new(ServerGroups::OwnerChatId).big_integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_servers_owner_chat_id")
+                    .table(Servers::Table)
+                    .col(Servers::OwnerChatId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_server_groups_owner_chat_id")
+                    .table(ServerGroups::Table)
+                    .col(ServerGroups::OwnerChatId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_server_groups_owner_chat_id")
+                    .table(ServerGroups::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_servers_owner_chat_id")
+                    .table(Servers::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ServerGroups::Table)
+                    .drop_column(ServerGroups::OwnerChatId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Servers::Table)
+                    .drop_column(Servers::OwnerChatId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Servers {
+    Table,
+    OwnerChatId,
+}
+
+#[derive(DeriveIden)]
+enum ServerGroups {
+    Table,
+    OwnerChatId,
+}
@@ -0,0 +1,111 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Incidents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Incidents::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Incidents::ServerId).integer().not_null())
+                    .col(ColumnDef::new(Incidents::ChatId).big_integer().not_null())
+                    .col(
+                        ColumnDef::new(Incidents::OpenedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Incidents::AcknowledgedAt).timestamp())
+                    .col(ColumnDef::new(Incidents::AcknowledgedBy).big_integer())
+                    // NULL while the server is still down
+                    .col(ColumnDef::new(Incidents::ResolvedAt).timestamp())
+                    .col(
+                        ColumnDef::new(Incidents::LastAlertedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_incident_server")
+                            .from(Incidents::Table, Incidents::ServerId)
+                            .to(Servers::Table, Servers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_incidents_server_resolved_at")
+                    .table(Incidents::Table)
+                    .col(Incidents::ServerId)
+                    .col(Incidents::ResolvedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Results recorded while an incident is open point at it
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CheckResults::Table)
+                    .add_column(ColumnDef::new(CheckResults::IncidentId).integer())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(CheckResults::Table)
+                    .drop_column(CheckResults::IncidentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Incidents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Incidents {
+    Table,
+    Id,
+    ServerId,
+    ChatId,
+    OpenedAt,
+    AcknowledgedAt,
+    AcknowledgedBy,
+    ResolvedAt,
+    LastAlertedAt,
+}
+
+#[derive(DeriveIden)]
+enum Servers {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum CheckResults {
+    Table,
+    IncidentId,
+}
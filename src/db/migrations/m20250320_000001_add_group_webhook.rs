@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // NULL URL means the group has no webhook
+        for column in [ServerGroups::WebhookUrl, ServerGroups::WebhookSecret] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ServerGroups::Table)
+                        .add_column(ColumnDef::new(column).string())
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for column in [ServerGroups::WebhookUrl, ServerGroups::WebhookSecret] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(ServerGroups::Table)
+                        .drop_column(column)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ServerGroups {
+    Table,
+    WebhookUrl,
+    WebhookSecret,
+}
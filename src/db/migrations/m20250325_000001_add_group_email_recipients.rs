@@ -0,0 +1,36 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Comma separated addresses, NULL means the group gets no mail
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ServerGroups::Table)
+                    .add_column(ColumnDef::new(ServerGroups::EmailRecipients).string())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ServerGroups::Table)
+                    .drop_column(ServerGroups::EmailRecipients)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ServerGroups {
+    Table,
+    EmailRecipients,
+}
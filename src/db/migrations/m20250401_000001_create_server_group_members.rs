@@ -0,0 +1,106 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ServerGroupMembers::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ServerGroupMembers::ServerId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ServerGroupMembers::GroupId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ServerGroupMembers::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ServerGroupMembers::ServerId)
+                            .col(ServerGroupMembers::GroupId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_server_group_member_server")
+                            .from(ServerGroupMembers::Table, ServerGroupMembers::ServerId)
+                            .to(Servers::Table, Servers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_server_group_member_group")
+                            .from(ServerGroupMembers::Table, ServerGroupMembers::GroupId)
+                            .to(ServerGroups::Table, ServerGroups::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_server_group_members_group_id")
+                    .table(ServerGroupMembers::Table)
+                    .col(ServerGroupMembers::GroupId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Carry over the single group of each server. servers.group_id stays
+        // behind unused, SQLite cannot drop a column with a foreign key.
+        let copy = Query::insert()
+            .into_table(ServerGroupMembers::Table)
+            .columns([ServerGroupMembers::ServerId, ServerGroupMembers::GroupId])
+            .select_from(
+                Query::select()
+                    .columns([Servers::Id, Servers::GroupId])
+                    .from(Servers::Table)
+                    .and_where(Expr::col(Servers::GroupId).is_not_null())
+                    .to_owned(),
+            )
+            .map_err(|e| DbErr::Migration(e.to_string()))?
+            .to_owned();
+        manager.exec_stmt(copy).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ServerGroupMembers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ServerGroupMembers {
+    Table,
+    ServerId,
+    GroupId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Servers {
+    Table,
+    Id,
+    GroupId,
+}
+
+#[derive(DeriveIden)]
+enum ServerGroups {
+    Table,
+    Id,
+}
@@ -0,0 +1,46 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // One row per chat in the middle of a wizard, the state is stored as JSON
+        manager
+            .create_table(
+                Table::create()
+                    .table(Dialogues::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Dialogues::ChatId)
+                            .big_integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Dialogues::State).text().not_null())
+                    .col(
+                        ColumnDef::new(Dialogues::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Dialogues::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Dialogues {
+    Table,
+    ChatId,
+    State,
+    UpdatedAt,
+}
@@ -2,6 +2,23 @@ use sea_orm_migration::prelude::*;
 
 mod m20240301_000001_create_server_groups;
 mod m20240301_000002_create_servers;
+mod m20250201_000001_add_server_last_status;
+mod m20250201_000002_create_check_results;
+mod m20250205_000001_add_server_check_kind;
+mod m20250210_000001_add_server_certificate;
+mod m20250215_000001_add_server_check_settings;
+mod m20250220_000001_create_monitor_settings;
+mod m20250225_000001_create_monitor_subscriptions;
+mod m20250301_000001_add_owner_chat_id;
+mod m20250305_000001_create_user_roles;
+mod m20250310_000001_add_server_thresholds;
+mod m20250315_000001_create_incidents;
+mod m20250320_000001_add_group_webhook;
+mod m20250325_000001_add_group_email_recipients;
+mod m20250401_000001_create_server_group_members;
+mod m20250405_000001_add_group_description;
+mod m20250410_000001_create_dialogues;
+mod m20250415_000001_add_server_address_options;
 
 pub struct Migrator;
 
@@ -11,6 +28,23 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20240301_000001_create_server_groups::Migration),
             Box::new(m20240301_000002_create_servers::Migration),
+            Box::new(m20250201_000001_add_server_last_status::Migration),
+            Box::new(m20250201_000002_create_check_results::Migration),
+            Box::new(m20250205_000001_add_server_check_kind::Migration),
+            Box::new(m20250210_000001_add_server_certificate::Migration),
+            Box::new(m20250215_000001_add_server_check_settings::Migration),
+            Box::new(m20250220_000001_create_monitor_settings::Migration),
+            Box::new(m20250225_000001_create_monitor_subscriptions::Migration),
+            Box::new(m20250301_000001_add_owner_chat_id::Migration),
+            Box::new(m20250305_000001_create_user_roles::Migration),
+            Box::new(m20250310_000001_add_server_thresholds::Migration),
+            Box::new(m20250315_000001_create_incidents::Migration),
+            Box::new(m20250320_000001_add_group_webhook::Migration),
+            Box::new(m20250325_000001_add_group_email_recipients::Migration),
+            Box::new(m20250401_000001_create_server_group_members::Migration),
+            Box::new(m20250405_000001_add_group_description::Migration),
+            Box::new(m20250410_000001_create_dialogues::Migration),
+            Box::new(m20250415_000001_add_server_address_options::Migration),
         ]
     }
 }
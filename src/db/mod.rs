@@ -1,6 +1,7 @@
 use sea_orm::*;
 use sea_orm_migration::MigratorTrait;
 
+pub mod dialogue_storage;
 pub mod entities;
 pub mod migrations;
 
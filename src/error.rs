@@ -10,13 +10,16 @@ pub enum BotError {
     Telegram(#[from] RequestError),
 
     #[error("Dialogue error: {0}")]
-    Dialogue(#[from] teloxide::dispatching::dialogue::InMemStorageError),
+    Dialogue(String),
 
     #[error("Environment error: {0}")]
     Environment(String),
 
     #[error("Server check error: {0}")]
     ServerCheck(String),
+
+    #[error("Notification error: {0}")]
+    Notify(String),
 }
 
 impl From<BotError> for RequestError {
@@ -4,16 +4,21 @@ mod config;
 mod db;
 mod error;
 mod monitor;
+mod notify;
 mod services;
 
 use crate::bot::ServerFatherBot;
-use crate::commands::State;
+use crate::commands::{State, StateStorage};
 use crate::config::Config;
 use crate::db::Database;
 use crate::error::Result;
-use crate::services::{group::GroupService, server::ServerService};
+use crate::notify::email::Mailer;
+use crate::services::{
+    check::CheckService, group::GroupService, incident::IncidentService, role::RoleService,
+    server::ServerService, settings::SettingsService, subscription::SubscriptionService,
+};
 use std::sync::Arc;
-use teloxide::dispatching::dialogue::InMemStorage;
+use std::time::Duration;
 use teloxide::prelude::*;
 
 #[tokio::main]
@@ -29,20 +34,76 @@ async fn main() -> Result<()> {
 
     let server_service = ServerService::new(database.connection.clone());
     let group_service = GroupService::new(database.connection.clone());
+    let check_service = CheckService::new(database.connection.clone());
+    let settings_service = SettingsService::new(database.connection.clone());
+    let subscription_service = SubscriptionService::new(database.connection.clone());
+    let role_service = RoleService::new(database.connection.clone());
+    let incident_service = IncidentService::new(database.connection.clone());
+
+    match config.default_owner_chat_id {
+        Some(owner_chat_id) => {
+            let servers = server_service.adopt_unowned(owner_chat_id).await?;
+            let groups = group_service.adopt_unowned(owner_chat_id).await?;
+            if servers + groups > 0 {
+                tracing::info!(
+                    "Assigned {} server(s) and {} group(s) without owner to chat {}",
+                    servers,
+                    groups,
+                    owner_chat_id
+                );
+            }
+        }
+        None => tracing::info!(
+            "DEFAULT_OWNER_CHAT_ID not set, servers and groups without owner stay hidden"
+        ),
+    }
+
+    if config.allowed_ids.is_empty() {
+        tracing::warn!("ALLOWED_IDS not set, every Telegram user can view server status");
+    }
+    if config.admin_ids.is_empty() {
+        tracing::warn!("ADMIN_IDS not set, only users granted a role before can change servers");
+    }
+
+    let dialogue_ttl = (config.dialogue_ttl > 0).then(|| Duration::from_secs(config.dialogue_ttl));
+    let dialogues = StateStorage::new(database.connection.clone(), dialogue_ttl);
+    if dialogue_ttl.is_some() {
+        tokio::spawn(commands::expire_dialogues(bot.clone(), dialogues.clone()));
+    }
+
+    let mailer = Mailer::from_config(&config)?;
+    if mailer.is_none() {
+        tracing::info!("SMTP_HOST not set, alerts are not mailed");
+    }
 
     let bot_instance = Arc::new(ServerFatherBot::new(
         bot.clone(),
         config,
         server_service,
         group_service,
+        check_service,
+        settings_service,
+        subscription_service,
+        role_service,
+        incident_service,
+        mailer,
     ));
 
-    let handler = Update::filter_message()
-        .enter_dialogue::<Message, InMemStorage<State>, State>()
-        .chain(commands::schema());
+    if let Err(e) = bot_instance.resume_monitoring().await {
+        tracing::error!("Failed to resume monitoring: {}", e);
+    }
+
+    let handler = dptree::entry()
+        .branch(
+            Update::filter_message()
+                .enter_dialogue::<Message, StateStorage, State>()
+                .chain(commands::authorize())
+                .chain(commands::schema()),
+        )
+        .branch(commands::callback_schema());
 
     Dispatcher::builder(bot, handler)
-        .dependencies(dptree::deps![InMemStorage::<State>::new(), bot_instance])
+        .dependencies(dptree::deps![dialogues, bot_instance])
         .enable_ctrlc_handler()
         .build()
         .dispatch()
@@ -0,0 +1,293 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::net::lookup_host;
+use tokio::time::timeout;
+
+const MAX_HOSTNAME_LEN: usize = 253;
+const MAX_LABEL_LEN: usize = 63;
+
+/// Host of a server as typed by the user, optionally with a port pasted along:
+/// `example.com`, `10.0.0.5:8080`, `2001:db8::1` or `[2001:db8::1]:443`.
+/// Hostnames are lowercased, IPv6 addresses are kept without brackets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HostInput {
+    pub host: String,
+    pub port: Option<u16>,
+}
+
+impl FromStr for HostInput {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("The host cannot be empty".to_string());
+        }
+        if let Some((scheme, _)) = s.split_once("://") {
+            return Err(format!(
+                "Enter the host without {}://, the check type is set separately",
+                scheme
+            ));
+        }
+        if s.contains(|c: char| c.is_whitespace() || "/?#@".contains(c)) {
+            return Err(format!(
+                "Invalid host '{}', expected a domain or an IP address",
+                s
+            ));
+        }
+
+        if let Some(rest) = s.strip_prefix('[') {
+            let (ip, port) = rest
+                .split_once(']')
+                .ok_or_else(|| format!("Missing ] in '{}'", s))?;
+            let ip = ip
+                .parse::<Ipv6Addr>()
+                .map_err(|_| format!("Invalid IPv6 address '{}'", ip))?;
+            let port = match port {
+                "" => None,
+                port => Some(parse_port(port.strip_prefix(':').unwrap_or(port))?),
+            };
+            return Ok(Self {
+                host: ip.to_string(),
+                port,
+            });
+        }
+
+        // More than one colon without brackets can only be a bare IPv6 address
+        if s.matches(':').count() > 1 {
+            let ip = s
+                .parse::<Ipv6Addr>()
+                .map_err(|_| format!("Invalid IPv6 address '{}', use [address]:port", s))?;
+            return Ok(Self {
+                host: ip.to_string(),
+                port: None,
+            });
+        }
+
+        let (host, port) = match s.split_once(':') {
+            Some((host, port)) => (host, Some(parse_port(port)?)),
+            None => (s, None),
+        };
+
+        let host = if host.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            host.parse::<Ipv4Addr>()
+                .map_err(|_| format!("Invalid IPv4 address '{}'", host))?
+                .to_string()
+        } else {
+            parse_hostname(host)?
+        };
+
+        Ok(Self { host, port })
+    }
+}
+
+fn parse_port(port: &str) -> std::result::Result<u16, String> {
+    port.parse::<u16>()
+        .ok()
+        .filter(|port| *port > 0)
+        .ok_or_else(|| format!("Invalid port '{}', it has to be between 1 and 65535", port))
+}
+
+/// Checks a hostname against the DNS rules, a trailing dot is dropped.
+fn parse_hostname(host: &str) -> std::result::Result<String, String> {
+    let invalid = |reason: &str| format!("Invalid hostname '{}': {}", host, reason);
+
+    let name = host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase();
+    if name.len() > MAX_HOSTNAME_LEN {
+        return Err(invalid("too long"));
+    }
+    for label in name.split('.') {
+        if label.is_empty() {
+            return Err(invalid("empty label"));
+        }
+        if label.len() > MAX_LABEL_LEN {
+            return Err(invalid("label longer than 63 characters"));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(invalid("labels cannot start or end with -"));
+        }
+        // Underscores are not valid in hostnames but common in internal DNS names
+        if !label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(invalid("only letters, digits, - and _ are allowed"));
+        }
+    }
+
+    Ok(name)
+}
+
+/// Host and port as `host:port`, with brackets around IPv6 addresses.
+pub fn join(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// Addresses a host resolves to, an IP address resolves to itself.
+pub async fn resolve(host: &str, port: u16, timeout_duration: Duration) -> io::Result<Vec<IpAddr>> {
+    let addrs = timeout(timeout_duration, lookup_host((host, port)))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "lookup timed out"))??;
+
+    let mut ips = Vec::new();
+    for addr in addrs {
+        if !ips.contains(&addr.ip()) {
+            ips.push(addr.ip());
+        }
+    }
+    Ok(ips)
+}
+
+/// Whether the address is only reachable from within a private network:
+/// RFC 1918, shared, loopback, link-local and unique local ranges.
+pub fn is_private(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || (first == 100 && (64..128).contains(&second))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_private(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || (first & 0xfe00) == 0xfc00
+                    || (first & 0xffc0) == 0xfe80
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> std::result::Result<HostInput, String> {
+        s.parse()
+    }
+
+    fn host(host: &str, port: Option<u16>) -> HostInput {
+        HostInput {
+            host: host.to_string(),
+            port,
+        }
+    }
+
+    #[test]
+    fn hostnames() {
+        assert_eq!(parse("Example.COM."), Ok(host("example.com", None)));
+        assert_eq!(parse("  my_host.lan "), Ok(host("my_host.lan", None)));
+        assert!(parse("-bad.example.com").is_err());
+        assert!(parse("a..b").is_err());
+        assert!(parse(&format!("{}.com", "a".repeat(64))).is_err());
+        assert_eq!(
+            parse("bad!host"),
+            Err("Invalid hostname 'bad!host': only letters, digits, - and _ are allowed".into())
+        );
+    }
+
+    #[test]
+    fn host_with_port() {
+        assert_eq!(
+            parse("example.com:8080"),
+            Ok(host("example.com", Some(8080)))
+        );
+        assert_eq!(parse("10.0.0.5:22"), Ok(host("10.0.0.5", Some(22))));
+    }
+
+    #[test]
+    fn ports_out_of_range() {
+        assert!(parse("example.com:0").is_err());
+        assert!(parse("example.com:65536").is_err());
+        assert!(parse("example.com:").is_err());
+        assert!(parse("[::1]:70000").is_err());
+    }
+
+    #[test]
+    fn ipv4() {
+        assert_eq!(parse("192.168.1.1"), Ok(host("192.168.1.1", None)));
+        assert!(parse("256.1.1.1").is_err());
+        assert!(parse("1.2.3").is_err());
+    }
+
+    #[test]
+    fn ipv6() {
+        assert_eq!(parse("::1"), Ok(host("::1", None)));
+        assert_eq!(parse("[::1]:443"), Ok(host("::1", Some(443))));
+        assert_eq!(parse("[::1]"), Ok(host("::1", None)));
+        assert_eq!(parse("2001:DB8:0:0:0:0:0:1"), Ok(host("2001:db8::1", None)));
+        assert!(parse("[::1").is_err());
+        assert!(parse("[example.com]:80").is_err());
+        assert!(parse("2001:db8::zz").is_err());
+    }
+
+    #[test]
+    fn rejects_schemes_and_paths() {
+        assert_eq!(
+            parse("https://example.com"),
+            Err("Enter the host without https://, the check type is set separately".into())
+        );
+        assert!(parse("example.com/health").is_err());
+        assert!(parse("user@example.com").is_err());
+        assert!(parse("exa mple.com").is_err());
+    }
+
+    #[test]
+    fn rejects_empty() {
+        assert_eq!(parse(""), Err("The host cannot be empty".into()));
+        assert!(parse("   ").is_err());
+    }
+
+    #[test]
+    fn join_brackets_ipv6() {
+        assert_eq!(join("example.com", 443), "example.com:443");
+        assert_eq!(join("10.0.0.5", 22), "10.0.0.5:22");
+        assert_eq!(join("2001:db8::1", 443), "[2001:db8::1]:443");
+    }
+
+    #[test]
+    fn private_ipv4_ranges() {
+        for ip in [
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.0.1",
+            "100.64.0.1",
+            "127.0.0.1",
+            "169.254.1.1",
+            "0.0.0.0",
+        ] {
+            assert!(is_private(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["8.8.8.8", "172.32.0.1", "100.128.0.1", "1.1.1.1"] {
+            assert!(!is_private(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn private_ipv6_ranges() {
+        for ip in [
+            "::1",
+            "::",
+            "fd00::1",
+            "fc00::1",
+            "fe80::1",
+            "::ffff:10.0.0.1",
+        ] {
+            assert!(is_private(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["2001:4860:4860::8888", "2606:4700::1111", "::ffff:8.8.8.8"] {
+            assert!(!is_private(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// How many results it takes to change a server's state, and what counts as flapping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Thresholds {
+    /// Consecutive failed checks before an online server is down
+    pub failures: u32,
+    /// Consecutive successful checks before an offline server is up
+    pub recoveries: u32,
+    /// State changes within `flap_window` that make a server flapping, 0 never flaps
+    pub flap_changes: usize,
+    pub flap_window: Duration,
+}
+
+/// What a check result did to the confirmed state of a server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transition {
+    /// The state changed and should be announced
+    Changed { is_up: bool },
+    /// The state changes too often, alerts stop until it settles
+    Flapping { is_up: bool },
+    /// No change for a whole window after flapping
+    Settled { is_up: bool },
+}
+
+/// Confirmed state of one server, fed with every check result.
+#[derive(Clone, Debug, Default)]
+pub struct StatusTracker {
+    status: Option<bool>,
+    streak: u32,
+    changes: VecDeque<Instant>,
+    flapping: bool,
+}
+
+impl StatusTracker {
+    /// Starts from a previously confirmed state, `None` takes the first result as is.
+    pub fn new(status: Option<bool>) -> Self {
+        Self {
+            status,
+            ..Default::default()
+        }
+    }
+
+    pub fn status(&self) -> Option<bool> {
+        self.status
+    }
+
+    pub fn is_flapping(&self) -> bool {
+        self.flapping
+    }
+
+    pub fn observe(
+        &mut self,
+        is_up: bool,
+        now: Instant,
+        thresholds: &Thresholds,
+    ) -> Option<Transition> {
+        while self
+            .changes
+            .front()
+            .is_some_and(|&changed| now.duration_since(changed) > thresholds.flap_window)
+        {
+            self.changes.pop_front();
+        }
+
+        let Some(status) = self.status else {
+            // First result only establishes the baseline
+            self.status = Some(is_up);
+            return None;
+        };
+
+        // A whole window without changes ends flapping
+        let settled = self.flapping && self.changes.is_empty();
+        if settled {
+            self.flapping = false;
+        }
+
+        if is_up == status {
+            self.streak = 0;
+            return settled.then_some(Transition::Settled { is_up });
+        }
+
+        self.streak += 1;
+        let needed = if is_up {
+            thresholds.recoveries
+        } else {
+            thresholds.failures
+        };
+        if self.streak < needed.max(1) {
+            return settled.then_some(Transition::Settled { is_up: status });
+        }
+
+        self.status = Some(is_up);
+        self.streak = 0;
+        self.changes.push_back(now);
+
+        if self.flapping {
+            None
+        } else if thresholds.flap_changes > 0 && self.changes.len() >= thresholds.flap_changes {
+            self.flapping = true;
+            Some(Transition::Flapping { is_up })
+        } else {
+            Some(Transition::Changed { is_up })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLDS: Thresholds = Thresholds {
+        failures: 2,
+        recoveries: 2,
+        flap_changes: 3,
+        flap_window: Duration::from_secs(600),
+    };
+
+    /// Feeds results one minute apart, returning the transitions.
+    fn feed(
+        tracker: &mut StatusTracker,
+        start: Instant,
+        results: &[bool],
+    ) -> Vec<Option<Transition>> {
+        results
+            .iter()
+            .enumerate()
+            .map(|(i, &is_up)| {
+                let now = start + Duration::from_secs(60 * i as u64);
+                tracker.observe(is_up, now, &THRESHOLDS)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_result_sets_baseline() {
+        let mut tracker = StatusTracker::new(None);
+        assert_eq!(tracker.observe(false, Instant::now(), &THRESHOLDS), None);
+        assert_eq!(tracker.status(), Some(false));
+    }
+
+    #[test]
+    fn single_failure_is_debounced() {
+        let mut tracker = StatusTracker::new(Some(true));
+        let transitions = feed(&mut tracker, Instant::now(), &[false, true, false, true]);
+        assert!(transitions.iter().all(Option::is_none));
+        assert_eq!(tracker.status(), Some(true));
+    }
+
+    #[test]
+    fn consecutive_failures_change_state() {
+        let mut tracker = StatusTracker::new(Some(true));
+        let transitions = feed(&mut tracker, Instant::now(), &[false, false, true, true]);
+        assert_eq!(
+            transitions,
+            [
+                None,
+                Some(Transition::Changed { is_up: false }),
+                None,
+                Some(Transition::Changed { is_up: true }),
+            ]
+        );
+        assert_eq!(tracker.status(), Some(true));
+    }
+
+    #[test]
+    fn zero_threshold_acts_like_one() {
+        let thresholds = Thresholds {
+            failures: 0,
+            ..THRESHOLDS
+        };
+        let mut tracker = StatusTracker::new(Some(true));
+        assert_eq!(
+            tracker.observe(false, Instant::now(), &thresholds),
+            Some(Transition::Changed { is_up: false })
+        );
+    }
+
+    #[test]
+    fn frequent_changes_start_flapping() {
+        let mut tracker = StatusTracker::new(Some(true));
+        let transitions = feed(
+            &mut tracker,
+            Instant::now(),
+            &[false, false, true, true, false, false, true, true],
+        );
+        assert_eq!(
+            transitions.into_iter().flatten().collect::<Vec<_>>(),
+            [
+                Transition::Changed { is_up: false },
+                Transition::Changed { is_up: true },
+                Transition::Flapping { is_up: false },
+            ]
+        );
+        assert!(tracker.is_flapping());
+        // Changes while flapping are not announced, the state still follows
+        assert_eq!(tracker.status(), Some(true));
+    }
+
+    #[test]
+    fn flapping_settles_after_quiet_window() {
+        let start = Instant::now();
+        let mut tracker = StatusTracker::new(Some(true));
+        feed(
+            &mut tracker,
+            start,
+            &[false, false, true, true, false, false],
+        );
+        assert!(tracker.is_flapping());
+
+        // Still inside the window of the last change
+        let now = start + Duration::from_secs(60 * 10);
+        assert_eq!(tracker.observe(false, now, &THRESHOLDS), None);
+        assert!(tracker.is_flapping());
+
+        let now = start + Duration::from_secs(60 * 5) + THRESHOLDS.flap_window * 2;
+        assert_eq!(
+            tracker.observe(false, now, &THRESHOLDS),
+            Some(Transition::Settled { is_up: false })
+        );
+        assert!(!tracker.is_flapping());
+
+        // Back to regular alerts
+        let transitions = feed(&mut tracker, now + Duration::from_secs(60), &[true, true]);
+        assert_eq!(transitions[1], Some(Transition::Changed { is_up: true }));
+    }
+
+    #[test]
+    fn settling_during_pending_change_reports_confirmed_state() {
+        let start = Instant::now();
+        let mut tracker = StatusTracker::new(Some(true));
+        feed(
+            &mut tracker,
+            start,
+            &[false, false, true, true, false, false],
+        );
+
+        let now = start + THRESHOLDS.flap_window * 2;
+        assert_eq!(
+            tracker.observe(true, now, &THRESHOLDS),
+            Some(Transition::Settled { is_up: false })
+        );
+        assert_eq!(tracker.status(), Some(false));
+    }
+
+    #[test]
+    fn flapping_disabled_with_zero_changes() {
+        let thresholds = Thresholds {
+            failures: 1,
+            recoveries: 1,
+            flap_changes: 0,
+            ..THRESHOLDS
+        };
+        let start = Instant::now();
+        let mut tracker = StatusTracker::new(Some(true));
+        for i in 0..10 {
+            let is_up = i % 2 == 1;
+            let now = start + Duration::from_secs(i);
+            assert_eq!(
+                tracker.observe(is_up, now, &thresholds),
+                Some(Transition::Changed { is_up })
+            );
+        }
+        assert!(!tracker.is_flapping());
+    }
+}
@@ -0,0 +1,351 @@
+use super::{error_kind, CheckOutcome};
+use crate::db::entities::check_result::ErrorKind;
+use crate::db::entities::server::{HttpMethod, Model as ServerModel};
+use crate::error::{BotError, Result};
+use reqwest::redirect::Policy;
+use serde::{Deserialize, Serialize};
+use std::error::Error as _;
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+const MAX_REDIRECTS: usize = 10;
+
+/// Inclusive range of HTTP status codes that count as healthy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl StatusRange {
+    pub fn contains(&self, status: u16) -> bool {
+        (self.min..=self.max).contains(&status)
+    }
+}
+
+impl Default for StatusRange {
+    fn default() -> Self {
+        Self { min: 200, max: 399 }
+    }
+}
+
+/// Parses `200`, `200-299` or a class like `2xx`, also in ranges as `2xx-3xx`.
+impl FromStr for StatusRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (min, max) = s.split_once('-').unwrap_or((s, s));
+        // A class stands for its first code at the start and its last at the end
+        let parse = |code: &str, class_offset: u16| {
+            let code = code.trim();
+            let parsed = match code.to_ascii_lowercase().strip_suffix("xx") {
+                Some(class) => class
+                    .parse::<u16>()
+                    .ok()
+                    .map(|class| class * 100 + class_offset),
+                None => code.parse::<u16>().ok(),
+            };
+            parsed
+                .filter(|code| (100..=599).contains(code))
+                .ok_or_else(|| format!("Invalid status code '{}'", code))
+        };
+
+        let range = Self {
+            min: parse(min, 0)?,
+            max: parse(max, 99)?,
+        };
+        if range.min > range.max {
+            return Err(format!("Invalid status range '{}'", s));
+        }
+
+        Ok(range)
+    }
+}
+
+impl fmt::Display for StatusRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.min == self.max {
+            write!(f, "{}", self.min)
+        } else {
+            write!(f, "{}-{}", self.min, self.max)
+        }
+    }
+}
+
+/// Settings of an HTTP(S) health check.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpCheck {
+    pub method: HttpMethod,
+    pub path: String,
+    pub expected_status: StatusRange,
+    pub follow_redirects: bool,
+}
+
+impl Default for HttpCheck {
+    fn default() -> Self {
+        Self {
+            method: HttpMethod::Get,
+            path: "/".into(),
+            expected_status: StatusRange::default(),
+            follow_redirects: false,
+        }
+    }
+}
+
+impl HttpCheck {
+    pub fn from_server(server: &ServerModel) -> Self {
+        Self {
+            method: server.http_method.unwrap_or_default(),
+            path: server.http_path.clone().unwrap_or_else(|| "/".into()),
+            expected_status: server
+                .expected_status
+                .as_deref()
+                .and_then(|range| range.parse().ok())
+                .unwrap_or_default(),
+            follow_redirects: server.follow_redirects,
+        }
+    }
+
+    /// Parses options like `HEAD /health 200-299 follow`, or spelled out as
+    /// `method=head path=/health status=2xx redirects=follow`. Tokens may come
+    /// in any order; missing ones keep their defaults and `default` keeps them all.
+    pub fn parse_options(input: &str) -> std::result::Result<Self, String> {
+        let mut check = Self::default();
+
+        for token in input.split_whitespace() {
+            if let Some((key, value)) = token.split_once('=') {
+                check.apply_option(key, value)?;
+                continue;
+            }
+
+            match token.to_ascii_lowercase().as_str() {
+                "default" => {}
+                "get" => check.method = HttpMethod::Get,
+                "head" => check.method = HttpMethod::Head,
+                "follow" => check.follow_redirects = true,
+                "nofollow" => check.follow_redirects = false,
+                _ if token.starts_with('/') => check.path = token.to_string(),
+                _ if token.starts_with(|c: char| c.is_ascii_digit()) => {
+                    check.expected_status = token.parse()?
+                }
+                _ => return Err(format!("Unknown option '{}'", token)),
+            }
+        }
+
+        Ok(check)
+    }
+
+    fn apply_option(&mut self, key: &str, value: &str) -> std::result::Result<(), String> {
+        match (
+            key.to_ascii_lowercase().as_str(),
+            value.to_ascii_lowercase().as_str(),
+        ) {
+            ("method", "get") => self.method = HttpMethod::Get,
+            ("method", "head") => self.method = HttpMethod::Head,
+            ("method", _) => return Err(format!("Unknown method '{}', use GET or HEAD", value)),
+            ("path", _) if value.starts_with('/') => self.path = value.to_string(),
+            ("path", _) => return Err(format!("Invalid path '{}', it has to start with /", value)),
+            ("status", _) => self.expected_status = value.parse()?,
+            ("redirects", "follow" | "yes" | "on") => self.follow_redirects = true,
+            ("redirects", "nofollow" | "no" | "off") => self.follow_redirects = false,
+            ("redirects", _) => {
+                return Err(format!(
+                    "Invalid redirects '{}', use follow or nofollow",
+                    value
+                ))
+            }
+            _ => return Err(format!("Unknown option '{}'", key)),
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for HttpCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let method = match self.method {
+            HttpMethod::Get => "GET",
+            HttpMethod::Head => "HEAD",
+        };
+        write!(
+            f,
+            "{} {} expecting {}{}",
+            method,
+            self.path,
+            self.expected_status,
+            if self.follow_redirects {
+                ", following redirects"
+            } else {
+                ""
+            }
+        )
+    }
+}
+
+/// Requests `url` from `host` over the given addresses, instead of resolving
+/// the host again. Redirects to other hosts are resolved as usual.
+pub async fn check_http(
+    url: &str,
+    host: &str,
+    addrs: &[SocketAddr],
+    check: &HttpCheck,
+    timeout_duration: Duration,
+) -> Result<CheckOutcome> {
+    let mut client = reqwest::Client::builder()
+        .redirect(if check.follow_redirects {
+            Policy::limited(MAX_REDIRECTS)
+        } else {
+            Policy::none()
+        })
+        .timeout(timeout_duration);
+    // IP literals are connected to directly anyway
+    if host.parse::<IpAddr>().is_err() {
+        client = client.resolve_to_addrs(host, addrs);
+    }
+    let client = client
+        .build()
+        .map_err(|e| BotError::ServerCheck(e.to_string()))?;
+
+    let method = match check.method {
+        HttpMethod::Get => reqwest::Method::GET,
+        HttpMethod::Head => reqwest::Method::HEAD,
+    };
+
+    let started = Instant::now();
+    let outcome = match client.request(method, url).send().await {
+        Ok(response) if check.expected_status.contains(response.status().as_u16()) => {
+            CheckOutcome::up(started.elapsed())
+        }
+        Ok(_) => CheckOutcome {
+            latency: Some(started.elapsed()),
+            ..CheckOutcome::down(ErrorKind::Status)
+        },
+        Err(e) => CheckOutcome::down(request_error_kind(&e)),
+    };
+
+    Ok(outcome)
+}
+
+fn request_error_kind(err: &reqwest::Error) -> ErrorKind {
+    if err.is_timeout() {
+        return ErrorKind::Timeout;
+    }
+
+    // Connection failures carry the underlying io::Error somewhere down the chain
+    let mut source = err.source();
+    while let Some(cause) = source {
+        if let Some(io_err) = cause.downcast_ref::<io::Error>() {
+            return error_kind(io_err);
+        }
+        source = cause.source();
+    }
+
+    ErrorKind::Other
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(s: &str) -> std::result::Result<StatusRange, String> {
+        s.parse()
+    }
+
+    #[test]
+    fn single_codes_and_ranges() {
+        assert_eq!(range("200"), Ok(StatusRange { min: 200, max: 200 }));
+        assert_eq!(range("200-399"), Ok(StatusRange { min: 200, max: 399 }));
+        assert_eq!(range(" 204 - 204 "), Ok(StatusRange { min: 204, max: 204 }));
+    }
+
+    #[test]
+    fn status_classes() {
+        assert_eq!(range("2xx"), Ok(StatusRange { min: 200, max: 299 }));
+        assert_eq!(range("2XX-3xx"), Ok(StatusRange { min: 200, max: 399 }));
+        assert_eq!(range("200-3xx"), Ok(StatusRange { min: 200, max: 399 }));
+        assert!(range("6xx").is_err());
+        assert!(range("0xx").is_err());
+        assert!(range("xx").is_err());
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        assert_eq!(
+            range("399-200"),
+            Err("Invalid status range '399-200'".into())
+        );
+        assert_eq!(range("99"), Err("Invalid status code '99'".into()));
+        assert!(range("600").is_err());
+        assert!(range("200-600").is_err());
+        assert!(range("ok").is_err());
+        assert!(range("").is_err());
+        assert!(range("200-").is_err());
+    }
+
+    #[test]
+    fn range_display_round_trips() {
+        for s in ["200", "200-399"] {
+            assert_eq!(range(s).unwrap().to_string(), s);
+        }
+        assert!(StatusRange::default().contains(301));
+        assert!(!StatusRange::default().contains(404));
+    }
+
+    #[test]
+    fn default_options() {
+        assert_eq!(
+            HttpCheck::parse_options("default"),
+            Ok(HttpCheck::default())
+        );
+        assert_eq!(HttpCheck::parse_options(""), Ok(HttpCheck::default()));
+    }
+
+    #[test]
+    fn positional_options() {
+        let check = HttpCheck::parse_options("HEAD /health 200-299 follow").unwrap();
+        assert_eq!(
+            check,
+            HttpCheck {
+                method: HttpMethod::Head,
+                path: "/health".into(),
+                expected_status: StatusRange { min: 200, max: 299 },
+                follow_redirects: true,
+            }
+        );
+        // Any order, the last one wins
+        let check = HttpCheck::parse_options("2xx follow get /ping nofollow").unwrap();
+        assert_eq!(check.method, HttpMethod::Get);
+        assert_eq!(check.path, "/ping");
+        assert!(!check.follow_redirects);
+    }
+
+    #[test]
+    fn keyed_options() {
+        let check =
+            HttpCheck::parse_options("method=HEAD path=/health status=2xx redirects=follow")
+                .unwrap();
+        assert_eq!(check.method, HttpMethod::Head);
+        assert_eq!(check.path, "/health");
+        assert_eq!(check.expected_status, StatusRange { min: 200, max: 299 });
+        assert!(check.follow_redirects);
+
+        let check = HttpCheck::parse_options("follow redirects=no").unwrap();
+        assert!(!check.follow_redirects);
+    }
+
+    #[test]
+    fn invalid_options() {
+        assert_eq!(
+            HttpCheck::parse_options("POST"),
+            Err("Unknown option 'POST'".into())
+        );
+        assert!(HttpCheck::parse_options("method=post").is_err());
+        assert!(HttpCheck::parse_options("path=health").is_err());
+        assert!(HttpCheck::parse_options("redirects=maybe").is_err());
+        assert!(HttpCheck::parse_options("status=500-200").is_err());
+        assert!(HttpCheck::parse_options("colour=red").is_err());
+        assert!(HttpCheck::parse_options("/health 700").is_err());
+    }
+}
@@ -1,16 +1,355 @@
+pub mod address;
+pub mod flap;
+pub mod http;
 pub mod tasks;
+pub mod tls;
 
+use crate::db::entities::check_result::ErrorKind;
+use crate::db::entities::server::{AddressFamily, CheckKind, Model as ServerModel};
 use crate::error::Result;
-use std::time::Duration;
+use futures::future::join_all;
+use sea_orm::ActiveEnum;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
 use tokio::net::TcpStream;
 use tokio::time::timeout;
 
-pub async fn check_server(host: &str, port: u16, timeout_duration: Duration) -> Result<bool> {
-    let addr = format!("{}:{}", host, port);
+/// Which servers a monitor watches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MonitorScope {
+    All,
+    Group(i32),
+}
+
+/// Runtime switches of a monitor task, changed while it runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MonitorControl {
+    pub paused: bool,
+    /// Overrides the chat's default check interval for this monitor
+    pub interval: Option<Duration>,
+}
+
+/// Result of a single probe against a server.
+#[derive(Clone, Debug)]
+pub struct CheckOutcome {
+    pub is_up: bool,
+    pub latency: Option<Duration>,
+    pub error: Option<ErrorKind>,
+    pub certificate: Option<tls::CertificateInfo>,
+    /// One entry per address when every resolved address is checked.
+    pub addresses: Vec<AddressOutcome>,
+}
+
+/// Result of probing one of several addresses of a server.
+#[derive(Clone, Debug)]
+pub struct AddressOutcome {
+    pub ip: IpAddr,
+    pub is_up: bool,
+    pub latency: Option<Duration>,
+    pub error: Option<ErrorKind>,
+}
+
+impl AddressOutcome {
+    /// `10.0.0.1 (12 ms)` or `10.0.0.2 (refused)`.
+    pub fn describe(&self) -> String {
+        match (self.latency, &self.error) {
+            (Some(latency), _) if self.is_up => format!("{} ({} ms)", self.ip, latency.as_millis()),
+            (_, Some(error)) => format!("{} ({})", self.ip, error.to_value()),
+            _ => self.ip.to_string(),
+        }
+    }
+}
+
+impl CheckOutcome {
+    fn up(latency: Duration) -> Self {
+        Self {
+            is_up: true,
+            latency: Some(latency),
+            error: None,
+            certificate: None,
+            addresses: Vec::new(),
+        }
+    }
+
+    fn down(error: ErrorKind) -> Self {
+        Self {
+            is_up: false,
+            latency: None,
+            error: Some(error),
+            certificate: None,
+            addresses: Vec::new(),
+        }
+    }
+
+    /// Merges the probes of every address. The server only counts as up when
+    /// all of them are, some but not all failing is a partial outage.
+    fn combine(outcomes: Vec<(IpAddr, CheckOutcome)>) -> Self {
+        let first_up = outcomes.iter().find(|(_, outcome)| outcome.is_up);
+        let first_down = outcomes.iter().find(|(_, outcome)| !outcome.is_up);
+
+        let mut combined = match (first_up, first_down) {
+            (Some((_, outcome)), None) => Self {
+                latency: outcomes
+                    .iter()
+                    .filter_map(|(_, outcome)| outcome.latency)
+                    .max(),
+                ..outcome.clone()
+            },
+            (None, Some((_, outcome))) => outcome.clone(),
+            (Some((_, outcome)), Some(_)) => Self {
+                is_up: false,
+                error: Some(ErrorKind::Partial),
+                ..outcome.clone()
+            },
+            (None, None) => Self::down(ErrorKind::Resolve),
+        };
+
+        combined.addresses = outcomes
+            .into_iter()
+            .map(|(ip, outcome)| AddressOutcome {
+                ip,
+                is_up: outcome.is_up,
+                latency: outcome.latency,
+                error: outcome.error,
+            })
+            .collect();
+        combined
+    }
+
+    /// Failing addresses, only known when every address was checked.
+    pub fn failed_addresses(&self) -> Vec<&AddressOutcome> {
+        self.addresses
+            .iter()
+            .filter(|address| !address.is_up)
+            .collect()
+    }
+}
+
+/// Probes a server the way its check kind asks for, over the addresses of its
+/// address family. With `check_all_addresses` every address is probed on its
+/// own, otherwise the first one that answers counts. Resolving and probing
+/// share the timeout.
+pub async fn probe(server: &ServerModel, timeout_duration: Duration) -> Result<CheckOutcome> {
+    let deadline = Instant::now() + timeout_duration;
+    let port = server.port as u16;
+    let ips = match address::resolve(&server.host, port, timeout_duration).await {
+        Ok(ips) => ips
+            .into_iter()
+            .filter(|ip| matches_family(*ip, server.address_family))
+            .collect::<Vec<_>>(),
+        Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+            return Ok(CheckOutcome::down(ErrorKind::Timeout))
+        }
+        Err(_) => return Ok(CheckOutcome::down(ErrorKind::Resolve)),
+    };
+    if ips.is_empty() {
+        return Ok(CheckOutcome::down(ErrorKind::Resolve));
+    }
+
+    let timeout_duration = deadline.saturating_duration_since(Instant::now());
+    if timeout_duration.is_zero() {
+        return Ok(CheckOutcome::down(ErrorKind::Timeout));
+    }
+
+    if !server.check_all_addresses {
+        let addrs = ips
+            .iter()
+            .map(|ip| SocketAddr::new(*ip, port))
+            .collect::<Vec<_>>();
+        return probe_addrs(server, &addrs, timeout_duration).await;
+    }
+
+    let probes = ips.iter().map(|ip| async move {
+        let outcome = probe_addrs(server, &[SocketAddr::new(*ip, port)], timeout_duration).await;
+        outcome.map(|outcome| (*ip, outcome))
+    });
+    let outcomes = join_all(probes)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    Ok(CheckOutcome::combine(outcomes))
+}
+
+async fn probe_addrs(
+    server: &ServerModel,
+    addrs: &[SocketAddr],
+    timeout_duration: Duration,
+) -> Result<CheckOutcome> {
+    match server.check_kind {
+        CheckKind::Tcp => check_server(addrs, timeout_duration).await,
+        CheckKind::Tls => tls::check_tls(&server.host, addrs, timeout_duration).await,
+        CheckKind::Http | CheckKind::Https => {
+            let check = http::HttpCheck::from_server(server);
+            http::check_http(
+                &server.target(),
+                &server.host,
+                addrs,
+                &check,
+                timeout_duration,
+            )
+            .await
+        }
+    }
+}
+
+fn matches_family(ip: IpAddr, family: Option<AddressFamily>) -> bool {
+    match family {
+        Some(AddressFamily::Ipv4) => ip.is_ipv4(),
+        Some(AddressFamily::Ipv6) => ip.is_ipv6(),
+        None => true,
+    }
+}
+
+/// Connects to the first address that accepts, names are resolved by the caller.
+pub async fn check_server(
+    addrs: &[SocketAddr],
+    timeout_duration: Duration,
+) -> Result<CheckOutcome> {
+    let probe = async {
+        let started = Instant::now();
+        match TcpStream::connect(addrs).await {
+            Ok(_) => CheckOutcome::up(started.elapsed()),
+            Err(e) => CheckOutcome::down(error_kind(&e)),
+        }
+    };
+
+    match timeout(timeout_duration, probe).await {
+        Ok(outcome) => Ok(outcome),
+        Err(_) => Ok(CheckOutcome::down(ErrorKind::Timeout)),
+    }
+}
+
+fn error_kind(err: &io::Error) -> ErrorKind {
+    match err.kind() {
+        io::ErrorKind::ConnectionRefused => ErrorKind::Refused,
+        io::ErrorKind::TimedOut => ErrorKind::Timeout,
+        io::ErrorKind::HostUnreachable | io::ErrorKind::NetworkUnreachable => {
+            ErrorKind::Unreachable
+        }
+        _ => ErrorKind::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use tokio::net::TcpListener;
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    fn up(millis: u64) -> CheckOutcome {
+        CheckOutcome::up(Duration::from_millis(millis))
+    }
+
+    fn server(host: &str, port: u16) -> ServerModel {
+        let now = Utc::now().naive_utc();
+        ServerModel {
+            id: 1,
+            name: "web".to_string(),
+            host: host.to_string(),
+            port: port as i32,
+            created_at: now,
+            updated_at: now,
+            last_check: None,
+            is_active: true,
+            last_status: None,
+            check_kind: CheckKind::Tcp,
+            http_method: None,
+            http_path: None,
+            expected_status: None,
+            follow_redirects: false,
+            cert_sni: None,
+            cert_issuer: None,
+            cert_not_after: None,
+            cert_alerted_days: None,
+            check_interval: None,
+            check_timeout: None,
+            check_retries: None,
+            owner_chat_id: None,
+            failure_threshold: None,
+            recovery_threshold: None,
+            address_family: None,
+            check_all_addresses: false,
+        }
+    }
+
+    #[test]
+    fn all_addresses_up() {
+        let outcome =
+            CheckOutcome::combine(vec![(ip("10.0.0.1"), up(5)), (ip("10.0.0.2"), up(20))]);
+        assert!(outcome.is_up);
+        assert_eq!(outcome.latency, Some(Duration::from_millis(20)));
+        assert_eq!(outcome.addresses.len(), 2);
+        assert!(outcome.failed_addresses().is_empty());
+    }
+
+    #[test]
+    fn one_address_down_is_partial() {
+        let outcome = CheckOutcome::combine(vec![
+            (ip("10.0.0.1"), up(5)),
+            (ip("10.0.0.2"), CheckOutcome::down(ErrorKind::Refused)),
+        ]);
+        assert!(!outcome.is_up);
+        assert_eq!(outcome.error, Some(ErrorKind::Partial));
+        let failed = outcome.failed_addresses();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].describe(), "10.0.0.2 (refused)");
+    }
+
+    #[test]
+    fn all_addresses_down() {
+        let outcome = CheckOutcome::combine(vec![
+            (ip("10.0.0.1"), CheckOutcome::down(ErrorKind::Timeout)),
+            (ip("::1"), CheckOutcome::down(ErrorKind::Refused)),
+        ]);
+        assert!(!outcome.is_up);
+        assert_eq!(outcome.error, Some(ErrorKind::Timeout));
+        assert_eq!(outcome.failed_addresses().len(), 2);
+    }
+
+    #[test]
+    fn address_families() {
+        assert!(matches_family(ip("10.0.0.1"), None));
+        assert!(matches_family(ip("10.0.0.1"), Some(AddressFamily::Ipv4)));
+        assert!(!matches_family(ip("10.0.0.1"), Some(AddressFamily::Ipv6)));
+        assert!(matches_family(ip("::1"), Some(AddressFamily::Ipv6)));
+    }
+
+    #[tokio::test]
+    async fn probes_ipv4_listener() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut server = server("127.0.0.1", listener.local_addr().unwrap().port());
+
+        let outcome = probe(&server, TIMEOUT).await.unwrap();
+        assert!(outcome.is_up);
+        assert!(outcome.addresses.is_empty());
+
+        server.check_all_addresses = true;
+        let outcome = probe(&server, TIMEOUT).await.unwrap();
+        assert!(outcome.is_up);
+        assert_eq!(outcome.addresses.len(), 1);
+
+        server.address_family = Some(AddressFamily::Ipv6);
+        let outcome = probe(&server, TIMEOUT).await.unwrap();
+        assert_eq!(outcome.error, Some(ErrorKind::Resolve));
+    }
+
+    #[tokio::test]
+    async fn probes_ipv6_literal() {
+        // Hosts without IPv6 have nothing to test
+        let Ok(listener) = TcpListener::bind("[::1]:0").await else {
+            return;
+        };
+        let server = server("::1", listener.local_addr().unwrap().port());
+        assert_eq!(server.target(), format!("[::1]:{}", server.port));
 
-    match timeout(timeout_duration, TcpStream::connect(&addr)).await {
-        Ok(Ok(_)) => Ok(true),
-        Ok(Err(_)) => Ok(false),
-        Err(_) => Ok(false), // Timeout occurred
+        let outcome = probe(&server, TIMEOUT).await.unwrap();
+        assert!(outcome.is_up);
     }
 }
@@ -1,51 +1,237 @@
 use crate::bot::ServerFatherBot;
+use crate::db::entities::server::Model as ServerModel;
+use crate::monitor::flap::{StatusTracker, Transition};
+use crate::monitor::tls::{self, CertificateInfo};
+use crate::monitor::{MonitorControl, MonitorScope};
+use crate::notify::StatusEvent;
+use chrono::{NaiveDateTime, Utc};
+use std::collections::HashMap;
 use std::sync::Arc;
 use teloxide::{prelude::*, types::ChatId};
-use tokio::time::{sleep, Duration};
+use tokio::sync::watch;
+use tokio::time::{sleep, sleep_until, Duration, Instant};
 
-pub async fn monitor_servers(bot: Arc<ServerFatherBot>, chat_id: i64) {
-    let interval_secs = bot.config().check_interval;
+/// Longest the scheduler sleeps between rounds, so newly added servers and
+/// changed intervals are picked up without waiting a full interval.
+const MAX_IDLE: Duration = Duration::from_secs(30);
+
+pub async fn monitor_servers(
+    bot: Arc<ServerFatherBot>,
+    chat_id: i64,
+    scope: MonitorScope,
+    mut control: watch::Receiver<MonitorControl>,
+) {
     let chat_id = ChatId(chat_id);
 
+    // Confirmed status per server. Servers not seen yet start from the status
+    // persisted on the row, so a restart does not re-announce everything.
+    let mut trackers: HashMap<i32, StatusTracker> = HashMap::new();
+    // When each server was last checked, servers not in here are due now. Due times
+    // are derived every round so interval changes apply to running monitors.
+    let mut last_checked: HashMap<i32, Instant> = HashMap::new();
+    // Last edit of each server, an edited server starts over from its stored
+    // status, which is cleared when it points somewhere else now.
+    let mut edited_at: HashMap<i32, NaiveDateTime> = HashMap::new();
+
     loop {
-        let mut current_status = Vec::new();
+        while control.borrow_and_update().paused {
+            // The sender is dropped once the monitor is stopped
+            if control.changed().await.is_err() {
+                return;
+            }
+        }
 
-        let servers = match bot.server_service().list_servers().await {
+        let servers = match scope {
+            MonitorScope::All => bot.server_service().list_servers(chat_id.0).await,
+            MonitorScope::Group(group_id) => match bot
+                .server_service()
+                .list_servers_by_group(chat_id.0, group_id)
+                .await
+            {
+                Ok(servers) => Ok(bot.drop_shared_servers(chat_id, group_id, servers).await),
+                Err(e) => Err(e),
+            },
+        };
+        let servers = match servers {
             Ok(servers) => servers,
             Err(e) => {
                 let _ = bot
                     .bot()
                     .send_message(chat_id, format!("❌ Failed to fetch servers: {}", e))
                     .await;
+                sleep(MAX_IDLE).await;
                 continue;
             }
         };
 
-        // Check all servers and collect their status
-        for server in servers {
-            let is_up = bot.check_server_status(&server).await.unwrap_or(false);
-            current_status.push((server, is_up));
+        let interval_override = control.borrow().interval;
+        let default_interval = match interval_override {
+            Some(interval) => interval,
+            None => match bot.default_check_interval(chat_id).await {
+                Ok(interval) => interval,
+                Err(e) => {
+                    tracing::warn!("Failed to load check interval: {}", e);
+                    Duration::from_secs(bot.config().check_interval)
+                }
+            },
+        };
+        let next_due = |server: &ServerModel, last_checked: &HashMap<i32, Instant>| {
+            last_checked
+                .get(&server.id)
+                .map(|&checked| checked + bot.check_interval(server, default_interval))
+        };
+
+        let now = Instant::now();
+        last_checked.retain(|id, _| servers.iter().any(|server| server.id == *id));
+        trackers.retain(|id, _| servers.iter().any(|server| server.id == *id));
+        edited_at.retain(|id, _| servers.iter().any(|server| server.id == *id));
+        for server in &servers {
+            let edited = edited_at.insert(server.id, server.updated_at);
+            if edited.is_some_and(|edited| edited != server.updated_at) {
+                trackers.remove(&server.id);
+                last_checked.remove(&server.id);
+            }
         }
 
-        // Process status changes and send notifications
-        for (server, is_up) in current_status {
-            let status_text = if is_up { "🟢 Online" } else { "🔴 Offline" };
-            let message = format!(
-                "Status Change:\n{} *{}*\n`{}:{}`\nStatus: {}",
-                if is_up { "✅" } else { "❌" },
-                server.name,
-                server.host,
-                server.port,
-                status_text
-            );
+        let (due, waiting): (Vec<_>, Vec<_>) = servers
+            .into_iter()
+            .partition(|server| next_due(server, &last_checked).is_none_or(|due| due <= now));
+        let outcomes = bot.check_servers(&due).await;
+
+        for (server, outcome) in due.iter().zip(outcomes) {
+            last_checked.insert(server.id, now);
+
+            // No verdict within the deadline, keep the previous state
+            let Some(outcome) = outcome else {
+                continue;
+            };
+
+            let tracker = trackers
+                .entry(server.id)
+                .or_insert_with(|| StatusTracker::new(server.last_status));
+            let was_up = tracker.status();
+            let transition = tracker.observe(outcome.is_up, now, &bot.thresholds(server));
+            let is_up = tracker.status().unwrap_or(outcome.is_up);
+
+            // Incidents follow the confirmed state, also while alerts are paused for flapping
+            let incident = match (was_up, is_up) {
+                (Some(true), false) => bot
+                    .incident_service()
+                    .open(server.id, chat_id.0)
+                    .await
+                    .map(Some),
+                (Some(false), true) => bot.incident_service().resolve(server.id).await,
+                _ => Ok(None),
+            };
+            let incident = incident.unwrap_or_else(|e| {
+                tracing::warn!("Failed to update incident of '{}': {}", server.name, e);
+                None
+            });
 
-            let _ = bot
-                .bot()
-                .send_message(chat_id, message)
-                .parse_mode(teloxide::types::ParseMode::MarkdownV2)
-                .await;
+            if let Some(Transition::Changed { is_up }) = transition {
+                bot.dispatch_event(
+                    server,
+                    StatusEvent::status_change(
+                        server,
+                        was_up,
+                        is_up,
+                        outcome.latency,
+                        incident.as_ref().map(|incident| incident.id),
+                    ),
+                );
+            }
+
+            let notified = match transition {
+                Some(Transition::Changed { is_up }) => {
+                    let failed = outcome.failed_addresses();
+                    bot.notify_status_change(server, is_up, incident.as_ref(), &failed, chat_id)
+                        .await
+                }
+                Some(Transition::Flapping { is_up }) => {
+                    bot.notify_flapping(server, true, is_up, chat_id).await
+                }
+                Some(Transition::Settled { is_up }) => {
+                    bot.notify_flapping(server, false, is_up, chat_id).await
+                }
+                None if !is_up && !tracker.is_flapping() => {
+                    bot.remind_incident(server, chat_id).await
+                }
+                None => Ok(()),
+            };
+            if let Err(e) = notified {
+                tracing::warn!("Failed to notify status change of '{}': {}", server.name, e);
+            }
+
+            // Only the confirmed status is persisted, single blips stay in the history
+            if let Err(e) = bot.server_service().record_status(server.id, is_up).await {
+                tracing::warn!("Failed to record status of '{}': {}", server.name, e);
+            }
+
+            if let Some(certificate) = &outcome.certificate {
+                check_certificate(&bot, server, certificate, chat_id).await;
+            }
+        }
+
+        let wake_at = due
+            .iter()
+            .chain(&waiting)
+            .filter_map(|server| next_due(server, &last_checked))
+            .min()
+            .unwrap_or(now + MAX_IDLE)
+            .min(Instant::now() + MAX_IDLE);
+        sleep_until(wake_at).await;
+    }
+}
+
+/// Warns once per configured threshold as the certificate approaches expiry.
+async fn check_certificate(
+    bot: &ServerFatherBot,
+    server: &ServerModel,
+    certificate: &CertificateInfo,
+    chat_id: ChatId,
+) {
+    let days_left = certificate.days_left(Utc::now().naive_utc());
+    let level = tls::warning_level(days_left, &bot.config().cert_warning_days);
+    let alerted = if server.cert_not_after == Some(certificate.not_after) {
+        server.cert_alerted_days.map(i64::from)
+    } else {
+        None
+    };
+
+    let new_level = match (level, alerted) {
+        // Reached a threshold closer to expiry than the last warning
+        (Some(level), alerted) if alerted.is_none_or(|alerted| level < alerted) => {
+            if let Err(e) = bot
+                .notify_cert_expiry(server, certificate, days_left, chat_id)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to notify certificate expiry of '{}': {}",
+                    server.name,
+                    e
+                );
+                return;
+            }
+            bot.dispatch_event(
+                server,
+                StatusEvent::cert_expiry(server, certificate, days_left),
+            );
+            Some(level)
         }
+        // Outside every threshold again, e.g. after CERT_WARNING_DAYS changed
+        (None, Some(_)) => None,
+        _ => return,
+    };
 
-        sleep(Duration::from_secs(interval_secs)).await;
+    if let Err(e) = bot
+        .server_service()
+        .set_cert_alerted_days(server.id, new_level.map(|level| level as i32))
+        .await
+    {
+        tracing::warn!(
+            "Failed to record certificate warning of '{}': {}",
+            server.name,
+            e
+        );
     }
 }
@@ -0,0 +1,195 @@
+use super::{error_kind, CheckOutcome};
+use crate::db::entities::check_result::ErrorKind;
+use crate::error::{BotError, Result};
+use chrono::{DateTime, NaiveDateTime};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_native_tls::native_tls;
+use x509_parser::prelude::*;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Details of the leaf certificate presented during a TLS handshake.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CertificateInfo {
+    pub sni: String,
+    pub issuer: String,
+    pub not_after: NaiveDateTime,
+}
+
+impl CertificateInfo {
+    /// Whole days until expiry, rounded down. Negative from the moment the
+    /// certificate expires, so a few hours past `not_after` is already -1.
+    pub fn days_left(&self, now: NaiveDateTime) -> i64 {
+        let days = (self.not_after - now)
+            .num_seconds()
+            .div_euclid(SECONDS_PER_DAY);
+        if self.not_after <= now {
+            days.min(-1)
+        } else {
+            days
+        }
+    }
+}
+
+/// Completes a TLS handshake with `host` over the first of `addrs` that accepts
+/// and reads the leaf certificate.
+///
+/// Certificate validation is relaxed on purpose: an expired or mismatched
+/// certificate must still be inspected so it can be reported.
+pub async fn check_tls(
+    host: &str,
+    addrs: &[SocketAddr],
+    timeout_duration: Duration,
+) -> Result<CheckOutcome> {
+    let connector = native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .use_sni(host.parse::<IpAddr>().is_err())
+        .build()
+        .map_err(|e| BotError::ServerCheck(e.to_string()))?;
+    let connector = tokio_native_tls::TlsConnector::from(connector);
+
+    let handshake = async {
+        let started = Instant::now();
+        let stream = match TcpStream::connect(addrs).await {
+            Ok(stream) => stream,
+            Err(e) => return CheckOutcome::down(error_kind(&e)),
+        };
+
+        let stream = match connector.connect(host, stream).await {
+            Ok(stream) => stream,
+            Err(_) => return CheckOutcome::down(ErrorKind::Tls),
+        };
+        let latency = started.elapsed();
+
+        let certificate = stream
+            .get_ref()
+            .peer_certificate()
+            .ok()
+            .flatten()
+            .and_then(|certificate| certificate.to_der().ok())
+            .and_then(|der| parse_certificate(host, &der));
+
+        match certificate {
+            Some(certificate) => CheckOutcome {
+                certificate: Some(certificate),
+                ..CheckOutcome::up(latency)
+            },
+            None => CheckOutcome::down(ErrorKind::Tls),
+        }
+    };
+
+    match timeout(timeout_duration, handshake).await {
+        Ok(outcome) => Ok(outcome),
+        Err(_) => Ok(CheckOutcome::down(ErrorKind::Timeout)),
+    }
+}
+
+fn parse_certificate(sni: &str, der: &[u8]) -> Option<CertificateInfo> {
+    let (_, certificate) = X509Certificate::from_der(der).ok()?;
+
+    let issuer = certificate
+        .issuer()
+        .iter_common_name()
+        .next()
+        .and_then(|name| name.as_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| certificate.issuer().to_string());
+    let not_after =
+        DateTime::from_timestamp(certificate.validity().not_after.timestamp(), 0)?.naive_utc();
+
+    Some(CertificateInfo {
+        sni: sni.to_string(),
+        issuer,
+        not_after,
+    })
+}
+
+/// Smallest warning threshold (in days) the certificate has reached, or 0 once
+/// it has expired. `None` while it is still outside every threshold. A
+/// threshold of 0 would read as expired and is skipped.
+pub fn warning_level(days_left: i64, thresholds: &[i64]) -> Option<i64> {
+    if days_left < 0 {
+        return Some(0);
+    }
+
+    thresholds
+        .iter()
+        .copied()
+        .filter(|&threshold| threshold > 0 && days_left <= threshold)
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeDelta};
+
+    const THRESHOLDS: [i64; 4] = [30, 14, 7, 1];
+
+    fn certificate() -> CertificateInfo {
+        CertificateInfo {
+            sni: "example.com".to_string(),
+            issuer: "Test CA".to_string(),
+            not_after: NaiveDate::from_ymd_opt(2025, 6, 1)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+        }
+    }
+
+    fn days_left_at(offset: TimeDelta) -> i64 {
+        let certificate = certificate();
+        certificate.days_left(certificate.not_after - offset)
+    }
+
+    #[test]
+    fn days_left_rounds_down() {
+        assert_eq!(days_left_at(TimeDelta::days(30)), 30);
+        assert_eq!(
+            days_left_at(TimeDelta::days(30) - TimeDelta::seconds(1)),
+            29
+        );
+        assert_eq!(days_left_at(TimeDelta::hours(23)), 0);
+        assert_eq!(days_left_at(TimeDelta::seconds(1)), 0);
+    }
+
+    #[test]
+    fn expired_certificates_are_negative() {
+        assert_eq!(days_left_at(TimeDelta::zero()), -1);
+        assert_eq!(days_left_at(-TimeDelta::hours(1)), -1);
+        assert_eq!(days_left_at(-TimeDelta::days(1)), -1);
+        assert_eq!(days_left_at(-TimeDelta::days(1) - TimeDelta::hours(1)), -2);
+    }
+
+    #[test]
+    fn warning_level_picks_closest_threshold() {
+        assert_eq!(warning_level(45, &THRESHOLDS), None);
+        assert_eq!(warning_level(31, &THRESHOLDS), None);
+        assert_eq!(warning_level(30, &THRESHOLDS), Some(30));
+        assert_eq!(warning_level(20, &THRESHOLDS), Some(30));
+        assert_eq!(warning_level(14, &THRESHOLDS), Some(14));
+        assert_eq!(warning_level(3, &THRESHOLDS), Some(7));
+        assert_eq!(warning_level(1, &THRESHOLDS), Some(1));
+        assert_eq!(warning_level(0, &THRESHOLDS), Some(1));
+    }
+
+    #[test]
+    fn warning_level_of_expired_certificate() {
+        assert_eq!(warning_level(-1, &THRESHOLDS), Some(0));
+        assert_eq!(warning_level(-40, &[]), Some(0));
+        // Expired within the last day still moves past the 1 day warning
+        let level = warning_level(days_left_at(-TimeDelta::hours(2)), &THRESHOLDS);
+        assert!(level < warning_level(0, &THRESHOLDS));
+    }
+
+    #[test]
+    fn warning_level_ignores_zero_threshold() {
+        assert_eq!(warning_level(0, &[0, 7]), Some(7));
+        assert_eq!(warning_level(10, &[0]), None);
+        assert_eq!(warning_level(10, &[]), None);
+    }
+}
@@ -0,0 +1,479 @@
+use super::{EventKind, Notifier, ServerState, StatusEvent};
+use crate::config::{Config, SmtpSecurity};
+use crate::error::{BotError, Result};
+use async_trait::async_trait;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::sleep;
+
+const SUBJECT_PREFIX: &str = "[Server Father]";
+
+type Queued = (Vec<String>, StatusEvent);
+
+/// Sends alert mails over SMTP. Queued events are collected for the batch
+/// window, so a mass outage ends up in one mail per recipient list.
+#[derive(Clone)]
+pub struct Mailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    queue: mpsc::UnboundedSender<Queued>,
+}
+
+impl Mailer {
+    /// Sets up the transport and starts the batching task, `None` without `SMTP_HOST`.
+    /// Nothing connects until the first mail goes out.
+    pub fn from_config(config: &Config) -> Result<Option<Self>> {
+        let Some(host) = &config.smtp_host else {
+            return Ok(None);
+        };
+
+        let from = config
+            .smtp_from
+            .as_deref()
+            .unwrap_or_default()
+            .parse::<Mailbox>()
+            .map_err(|e| BotError::Environment(format!("Invalid SMTP_FROM: {}", e)))?;
+
+        let builder = match config.smtp_security {
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+            SmtpSecurity::Plain => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                host,
+            )),
+        }
+        .map_err(|e| BotError::Environment(format!("Invalid SMTP_HOST: {}", e)))?;
+
+        let mut builder = builder.port(config.smtp_port);
+        if let Some(username) = &config.smtp_username {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                config.smtp_password.clone().unwrap_or_default(),
+            ));
+        }
+
+        Ok(Some(Self::new(
+            builder.build(),
+            from,
+            Duration::from_secs(config.email_batch_window),
+        )))
+    }
+
+    fn new(
+        transport: AsyncSmtpTransport<Tokio1Executor>,
+        from: Mailbox,
+        batch_window: Duration,
+    ) -> Self {
+        let (queue, receiver) = mpsc::unbounded_channel();
+        let mailer = Self {
+            transport,
+            from,
+            queue,
+        };
+        tokio::spawn(mailer.clone().run_batches(receiver, batch_window));
+        mailer
+    }
+
+    /// Adds an event to the next batch for these recipients.
+    pub fn queue(&self, mut recipients: Vec<String>, event: StatusEvent) -> Result<()> {
+        recipients.sort();
+        recipients.dedup();
+        self.queue
+            .send((recipients, event))
+            .map_err(|_| BotError::Notify("Mail queue is closed".into()))
+    }
+
+    /// Mails the events right away as one message.
+    pub async fn send(&self, recipients: &[String], events: &[StatusEvent]) -> Result<()> {
+        let mut message = Message::builder()
+            .from(self.from.clone())
+            .subject(subject(events));
+        for recipient in recipients {
+            let mailbox = recipient.parse::<Mailbox>().map_err(|e| {
+                BotError::Notify(format!("Invalid recipient '{}': {}", recipient, e))
+            })?;
+            message = message.to(mailbox);
+        }
+
+        let message = message
+            .multipart(MultiPart::alternative_plain_html(
+                render_text(events),
+                render_html(events),
+            ))
+            .map_err(|e| BotError::Notify(format!("Failed to build mail: {}", e)))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| BotError::Notify(format!("SMTP delivery failed: {}", e)))?;
+        Ok(())
+    }
+
+    async fn run_batches(self, mut receiver: mpsc::UnboundedReceiver<Queued>, window: Duration) {
+        while let Some(first) = receiver.recv().await {
+            // Give the rest of an outage time to come in
+            sleep(window).await;
+
+            let mut batches: BTreeMap<Vec<String>, Vec<StatusEvent>> = BTreeMap::new();
+            let mut next = Some(first);
+            while let Some((recipients, event)) = next {
+                batches.entry(recipients).or_default().push(event);
+                next = receiver.try_recv().ok();
+            }
+
+            for (recipients, events) in batches {
+                if let Err(e) = self.send(&recipients, &events).await {
+                    tracing::warn!(
+                        "Failed to mail {} alert(s) to {}: {}",
+                        events.len(),
+                        recipients.join(", "),
+                        e
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Mails a group's events to its recipients through the shared [`Mailer`].
+pub struct EmailNotifier {
+    mailer: Mailer,
+    recipients: Vec<String>,
+}
+
+impl EmailNotifier {
+    pub fn new(mailer: Mailer, recipients: Vec<String>) -> Self {
+        Self { mailer, recipients }
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    /// Only queues the event, the batching task delivers and logs failures.
+    async fn notify(&self, event: &StatusEvent) -> Result<()> {
+        self.mailer.queue(self.recipients.clone(), event.clone())
+    }
+}
+
+/// Parses a recipient address, as accepted by `/setemail`.
+pub fn validate_address(address: &str) -> std::result::Result<(), String> {
+    address
+        .parse::<Mailbox>()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn subject(events: &[StatusEvent]) -> String {
+    if let [event] = events {
+        return format!("{} {}", SUBJECT_PREFIX, headline(event));
+    }
+
+    let count = |matches: fn(&StatusEvent) -> bool| events.iter().filter(|e| matches(e)).count();
+    let parts = [
+        (count(|e| is_status(e, ServerState::Down)), "down"),
+        (count(|e| is_status(e, ServerState::Up)), "up"),
+        (
+            count(|e| e.event == EventKind::CertExpiry),
+            "certificate warning(s)",
+        ),
+        (count(|e| e.event == EventKind::Test), "test(s)"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, label)| format!("{} {}", count, label))
+    .collect::<Vec<_>>();
+
+    format!(
+        "{} {} alerts: {}",
+        SUBJECT_PREFIX,
+        events.len(),
+        parts.join(", ")
+    )
+}
+
+fn is_status(event: &StatusEvent, state: ServerState) -> bool {
+    event.event == EventKind::StatusChange && event.new_state == state
+}
+
+/// One line summary of an event, also the subject of single event mails.
+fn headline(event: &StatusEvent) -> String {
+    let name = &event.server.name;
+    match (event.event, &event.certificate) {
+        (EventKind::CertExpiry, Some(certificate)) if certificate.days_left < 0 => {
+            format!("Certificate of {} has expired", name)
+        }
+        (EventKind::CertExpiry, Some(certificate)) => format!(
+            "Certificate of {} expires in {} day(s)",
+            name, certificate.days_left
+        ),
+        (EventKind::Test, _) => "Test alert".to_string(),
+        _ => match event.new_state {
+            ServerState::Down => format!("{} is down", name),
+            ServerState::Up => format!("{} is back up", name),
+        },
+    }
+}
+
+/// Label and value pairs shown under the headline.
+fn details(event: &StatusEvent) -> Vec<(&'static str, String)> {
+    let mut details = vec![("Target", event.server.target.clone())];
+    if let Some(group) = &event.group {
+        details.push(("Group", group.name.clone()));
+    }
+    if let Some(certificate) = &event.certificate {
+        details.push(("Certificate", certificate.sni.clone()));
+        details.push(("Issuer", certificate.issuer.clone()));
+        details.push((
+            "Expiry",
+            certificate
+                .not_after
+                .format("%Y-%m-%d %H:%M UTC")
+                .to_string(),
+        ));
+    }
+    if let Some(latency) = event.latency_ms {
+        details.push(("Latency", format!("{} ms", latency)));
+    }
+    if let Some(incident_id) = event.incident_id {
+        details.push(("Incident", format!("#{}", incident_id)));
+    }
+    details.push((
+        "Time",
+        event.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+    ));
+    details
+}
+
+fn render_text(events: &[StatusEvent]) -> String {
+    let mut text = String::new();
+    for event in events {
+        text.push_str(&headline(event));
+        text.push('\n');
+        for (label, value) in details(event) {
+            text.push_str(&format!("  {}: {}\n", label, value));
+        }
+        text.push('\n');
+    }
+    text.push_str("-- \nSent by Server Father Bot\n");
+    text
+}
+
+fn render_html(events: &[StatusEvent]) -> String {
+    let mut html =
+        String::from("<!DOCTYPE html>\n<html><body style=\"font-family: sans-serif\">\n");
+    for event in events {
+        let color = match (event.event, event.new_state) {
+            (EventKind::CertExpiry, _) => "#b7791f",
+            (_, ServerState::Down) => "#c53030",
+            (_, ServerState::Up) => "#2f855a",
+        };
+        html.push_str(&format!(
+            "<h3 style=\"color: {}\">{}</h3>\n<table>\n",
+            color,
+            escape_html(&headline(event))
+        ));
+        for (label, value) in details(event) {
+            html.push_str(&format!(
+                "<tr><th align=\"left\">{}</th><td>{}</td></tr>\n",
+                label,
+                escape_html(&value)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("<p style=\"color: #718096\">Sent by Server Father Bot</p>\n</body></html>\n");
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{CertificateDetails, ServerInfo};
+    use chrono::{NaiveDate, Utc};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    const WINDOW: Duration = Duration::from_millis(200);
+
+    /// Accepts mails on a local port and keeps their raw data.
+    async fn smtp_sink() -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let mails = Arc::new(Mutex::new(Vec::new()));
+
+        let received = mails.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let received = received.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    writer.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        let command = line.to_ascii_uppercase();
+                        let reply: &[u8] = if command.starts_with("EHLO") {
+                            b"250 sink\r\n"
+                        } else if command.starts_with("DATA") {
+                            writer.write_all(b"354 go ahead\r\n").await.unwrap();
+                            let mut data = String::new();
+                            while let Ok(Some(line)) = lines.next_line().await {
+                                if line == "." {
+                                    break;
+                                }
+                                data.push_str(&line);
+                                data.push('\n');
+                            }
+                            received.lock().unwrap().push(data);
+                            b"250 queued\r\n"
+                        } else if command.starts_with("QUIT") {
+                            writer.write_all(b"221 bye\r\n").await.unwrap();
+                            break;
+                        } else {
+                            b"250 ok\r\n"
+                        };
+                        writer.write_all(reply).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        (port, mails)
+    }
+
+    fn mailer(port: u16) -> Mailer {
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous("127.0.0.1")
+            .port(port)
+            .build();
+        Mailer::new(transport, "bot@example.com".parse().unwrap(), WINDOW)
+    }
+
+    fn event(name: &str, kind: EventKind, new_state: ServerState) -> StatusEvent {
+        StatusEvent {
+            event: kind,
+            server: ServerInfo {
+                id: 1,
+                name: name.to_string(),
+                target: format!("{}.example.com:443", name),
+            },
+            group: None,
+            old_state: None,
+            new_state,
+            latency_ms: None,
+            timestamp: Utc::now(),
+            incident_id: None,
+            certificate: None,
+        }
+    }
+
+    fn cert_event(name: &str, days_left: i64) -> StatusEvent {
+        StatusEvent {
+            certificate: Some(CertificateDetails {
+                sni: format!("{}.example.com", name),
+                issuer: "Test CA".to_string(),
+                not_after: NaiveDate::from_ymd_opt(2025, 3, 1)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap(),
+                days_left,
+            }),
+            ..event(name, EventKind::CertExpiry, ServerState::Up)
+        }
+    }
+
+    fn subject_of(mail: &str) -> &str {
+        mail.lines()
+            .find_map(|line| line.strip_prefix("Subject: "))
+            .unwrap()
+    }
+
+    #[test]
+    fn subjects_name_the_event() {
+        let down = event("web", EventKind::StatusChange, ServerState::Down);
+        let up = event("web", EventKind::StatusChange, ServerState::Up);
+        assert_eq!(
+            subject(std::slice::from_ref(&down)),
+            "[Server Father] web is down"
+        );
+        assert_eq!(
+            subject(std::slice::from_ref(&up)),
+            "[Server Father] web is back up"
+        );
+        assert_eq!(
+            subject(&[cert_event("web", 7)]),
+            "[Server Father] Certificate of web expires in 7 day(s)"
+        );
+        assert_eq!(
+            subject(&[cert_event("web", -1)]),
+            "[Server Father] Certificate of web has expired"
+        );
+        assert_eq!(
+            subject(&[down.clone(), down, up, cert_event("db", 3)]),
+            "[Server Father] 4 alerts: 2 down, 1 up, 1 certificate warning(s)"
+        );
+    }
+
+    #[tokio::test]
+    async fn burst_is_mailed_once() {
+        let (port, mails) = smtp_sink().await;
+        let mailer = mailer(port);
+        let recipients = vec!["ops@example.com".to_string()];
+
+        for name in ["web", "db", "cache"] {
+            let event = event(name, EventKind::StatusChange, ServerState::Down);
+            mailer.queue(recipients.clone(), event).unwrap();
+        }
+        sleep(WINDOW * 5).await;
+
+        let mails = mails.lock().unwrap();
+        assert_eq!(mails.len(), 1);
+        assert_eq!(subject_of(&mails[0]), "[Server Father] 3 alerts: 3 down");
+    }
+
+    #[tokio::test]
+    async fn recipient_lists_get_a_mail_each() {
+        let (port, mails) = smtp_sink().await;
+        let mailer = mailer(port);
+
+        let down = event("web", EventKind::StatusChange, ServerState::Down);
+        mailer
+            .queue(vec!["ops@example.com".to_string()], down.clone())
+            .unwrap();
+        mailer
+            .queue(vec!["dev@example.com".to_string()], down)
+            .unwrap();
+        sleep(WINDOW * 5).await;
+
+        assert_eq!(mails.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn mails_have_plain_and_html_parts() {
+        let (port, mails) = smtp_sink().await;
+        let recipients = ["ops@example.com".to_string()];
+        let events = [event("web", EventKind::StatusChange, ServerState::Up)];
+
+        mailer(port).send(&recipients, &events).await.unwrap();
+
+        let mails = mails.lock().unwrap();
+        let mail = &mails[0];
+        assert_eq!(subject_of(mail), "[Server Father] web is back up");
+        assert!(mail.contains("Content-Type: multipart/alternative"));
+        assert!(mail.contains("Content-Type: text/plain"));
+        assert!(mail.contains("Content-Type: text/html"));
+        assert!(mail.contains("web.example.com:443"));
+    }
+}
@@ -0,0 +1,173 @@
+pub mod email;
+pub mod webhook;
+
+use crate::db::entities::server::Model as ServerModel;
+use crate::db::entities::server_group::Model as ServerGroupModel;
+use crate::error::Result;
+use crate::monitor::tls::CertificateInfo;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+
+/// A channel alerts are delivered through besides the Telegram chat.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Delivers an event, retrying as the channel sees fit.
+    async fn notify(&self, event: &StatusEvent) -> Result<()>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    StatusChange,
+    /// The TLS certificate reached a warning threshold
+    CertExpiry,
+    /// Sent when a channel is set up, to check it works
+    Test,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerState {
+    Up,
+    Down,
+}
+
+impl From<bool> for ServerState {
+    fn from(is_up: bool) -> Self {
+        if is_up {
+            ServerState::Up
+        } else {
+            ServerState::Down
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ServerInfo {
+    pub id: i32,
+    pub name: String,
+    pub target: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GroupInfo {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CertificateDetails {
+    pub sni: String,
+    pub issuer: String,
+    pub not_after: NaiveDateTime,
+    /// Negative once expired
+    pub days_left: i64,
+}
+
+/// What notifiers receive, serialized as is into webhook payloads.
+#[derive(Clone, Debug, Serialize)]
+pub struct StatusEvent {
+    pub event: EventKind,
+    pub server: ServerInfo,
+    pub group: Option<GroupInfo>,
+    pub old_state: Option<ServerState>,
+    pub new_state: ServerState,
+    pub latency_ms: Option<u64>,
+    pub timestamp: DateTime<Utc>,
+    pub incident_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub certificate: Option<CertificateDetails>,
+}
+
+impl StatusEvent {
+    pub fn status_change(
+        server: &ServerModel,
+        was_up: Option<bool>,
+        is_up: bool,
+        latency: Option<Duration>,
+        incident_id: Option<i32>,
+    ) -> Self {
+        Self {
+            event: EventKind::StatusChange,
+            server: ServerInfo::from(server),
+            group: None,
+            old_state: was_up.map(ServerState::from),
+            new_state: is_up.into(),
+            latency_ms: latency.map(|latency| latency.as_millis() as u64),
+            timestamp: Utc::now(),
+            incident_id,
+            certificate: None,
+        }
+    }
+
+    /// The server answered, but its certificate is close to or past expiry.
+    pub fn cert_expiry(
+        server: &ServerModel,
+        certificate: &CertificateInfo,
+        days_left: i64,
+    ) -> Self {
+        Self {
+            event: EventKind::CertExpiry,
+            server: ServerInfo::from(server),
+            group: None,
+            old_state: None,
+            new_state: ServerState::Up,
+            latency_ms: None,
+            timestamp: Utc::now(),
+            incident_id: None,
+            certificate: Some(CertificateDetails {
+                sni: certificate.sni.clone(),
+                issuer: certificate.issuer.clone(),
+                not_after: certificate.not_after,
+                days_left,
+            }),
+        }
+    }
+
+    /// A made up event for a group, so receivers can be tried out.
+    pub fn test(group: &ServerGroupModel) -> Self {
+        Self {
+            event: EventKind::Test,
+            server: ServerInfo {
+                id: 0,
+                name: "test".to_string(),
+                target: "example.com:443".to_string(),
+            },
+            group: Some(GroupInfo::from(group)),
+            old_state: Some(ServerState::Up),
+            new_state: ServerState::Down,
+            latency_ms: None,
+            timestamp: Utc::now(),
+            incident_id: None,
+            certificate: None,
+        }
+    }
+
+    pub fn with_group(self, group: &ServerGroupModel) -> Self {
+        Self {
+            group: Some(GroupInfo::from(group)),
+            ..self
+        }
+    }
+}
+
+impl From<&ServerModel> for ServerInfo {
+    fn from(server: &ServerModel) -> Self {
+        Self {
+            id: server.id,
+            name: server.name.clone(),
+            target: server.target(),
+        }
+    }
+}
+
+impl From<&ServerGroupModel> for GroupInfo {
+    fn from(group: &ServerGroupModel) -> Self {
+        Self {
+            id: group.id,
+            name: group.name.clone(),
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use super::{Notifier, StatusEvent};
+use crate::error::{BotError, Result};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Wait before the first retry, doubled for every further one.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Header carrying `sha256=<hex HMAC of the body>` when a secret is set.
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// POSTs events as JSON to a URL.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+    secret: Option<String>,
+    retries: u32,
+    /// Wait before the first retry, `INITIAL_BACKOFF` outside of tests
+    backoff: Duration,
+}
+
+impl WebhookNotifier {
+    pub fn new(
+        url: String,
+        secret: Option<String>,
+        retries: u32,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(concat!("server-father-bot/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| BotError::Notify(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            url,
+            secret,
+            retries,
+            backoff: INITIAL_BACKOFF,
+        })
+    }
+
+    /// Sends the body once. Errors worth another attempt come back as `Err(true, _)`.
+    async fn deliver(&self, body: &[u8]) -> std::result::Result<(), (bool, BotError)> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, body));
+        }
+
+        let response = request.send().await.map_err(|e| {
+            (
+                true,
+                BotError::Notify(format!("Webhook request failed: {}", e)),
+            )
+        })?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        // Server errors and rate limits may pass later, anything else will not
+        let retry = status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+        Err((
+            retry,
+            BotError::Notify(format!("Webhook answered with HTTP {}", status)),
+        ))
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, event: &StatusEvent) -> Result<()> {
+        let body = serde_json::to_vec(event)
+            .map_err(|e| BotError::Notify(format!("Failed to encode event: {}", e)))?;
+
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match self.deliver(&body).await {
+                Ok(()) => return Ok(()),
+                Err((true, e)) if attempt < self.retries => {
+                    tracing::warn!(
+                        "Webhook delivery to {} failed, retrying in {:?}: {}",
+                        self.url,
+                        backoff,
+                        e
+                    );
+                    sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    attempt += 1;
+                }
+                Err((_, e)) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Signs a body the way receivers verify it: `sha256=` and the hex HMAC-SHA256.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    let digest = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+    format!("sha256={}", digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{EventKind, ServerInfo, ServerState};
+    use chrono::Utc;
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::time::Instant;
+
+    struct Request {
+        headers: HashMap<String, String>,
+        body: Vec<u8>,
+        received_at: Instant,
+    }
+
+    /// Answers requests with `statuses` in turn, the last one repeated, and
+    /// keeps what it received.
+    async fn stand_in(statuses: &[u16]) -> (SocketAddr, Arc<Mutex<Vec<Request>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let statuses = statuses.to_vec();
+
+        let received = requests.clone();
+        tokio::spawn(async move {
+            for attempt in 0.. {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut stream = BufReader::new(stream);
+
+                let mut headers = HashMap::new();
+                let mut line = String::new();
+                stream.read_line(&mut line).await.unwrap();
+                loop {
+                    line.clear();
+                    stream.read_line(&mut line).await.unwrap();
+                    match line.trim_end().split_once(':') {
+                        Some((name, value)) => {
+                            headers.insert(name.to_ascii_lowercase(), value.trim().to_string());
+                        }
+                        None => break,
+                    }
+                }
+                let length = headers
+                    .get("content-length")
+                    .map_or(0, |length| length.parse().unwrap());
+                let mut body = vec![0; length];
+                stream.read_exact(&mut body).await.unwrap();
+                received.lock().unwrap().push(Request {
+                    headers,
+                    body,
+                    received_at: Instant::now(),
+                });
+
+                let status = statuses[attempt.min(statuses.len() - 1)];
+                let response = format!(
+                    "HTTP/1.1 {} Stand-in\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (addr, requests)
+    }
+
+    fn notifier(url: String, secret: Option<&str>, retries: u32) -> WebhookNotifier {
+        let mut notifier = WebhookNotifier::new(
+            url,
+            secret.map(str::to_string),
+            retries,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        notifier.backoff = Duration::from_millis(50);
+        notifier
+    }
+
+    fn event() -> StatusEvent {
+        StatusEvent {
+            event: EventKind::StatusChange,
+            server: ServerInfo {
+                id: 7,
+                name: "web".to_string(),
+                target: "example.com:443".to_string(),
+            },
+            group: None,
+            old_state: Some(ServerState::Up),
+            new_state: ServerState::Down,
+            latency_ms: None,
+            timestamp: Utc::now(),
+            incident_id: Some(3),
+            certificate: None,
+        }
+    }
+
+    #[test]
+    fn signs_with_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn posts_signed_json() {
+        let (addr, requests) = stand_in(&[204]).await;
+        let notifier = notifier(format!("http://{}/hook", addr), Some("s3cret"), 0);
+
+        notifier.notify(&event()).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.headers["content-type"], "application/json");
+        assert_eq!(
+            request.headers[&SIGNATURE_HEADER.to_ascii_lowercase()],
+            sign("s3cret", &request.body)
+        );
+
+        let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(payload["event"], "status_change");
+        assert_eq!(payload["server"]["id"], 7);
+        assert_eq!(payload["server"]["name"], "web");
+        assert_eq!(payload["server"]["target"], "example.com:443");
+        assert_eq!(payload["old_state"], "up");
+        assert_eq!(payload["new_state"], "down");
+        assert_eq!(payload["incident_id"], 3);
+        assert!(payload["group"].is_null());
+        assert!(payload["timestamp"].is_string());
+        assert!(payload.get("certificate").is_none());
+    }
+
+    #[tokio::test]
+    async fn unsigned_without_secret() {
+        let (addr, requests) = stand_in(&[200]).await;
+        notifier(format!("http://{}/", addr), None, 0)
+            .notify(&event())
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert!(!requests[0]
+            .headers
+            .contains_key(&SIGNATURE_HEADER.to_ascii_lowercase()));
+    }
+
+    #[tokio::test]
+    async fn retries_server_errors_with_backoff() {
+        let (addr, requests) = stand_in(&[500, 503, 502, 200]).await;
+        notifier(format!("http://{}/", addr), None, 3)
+            .notify(&event())
+            .await
+            .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 4);
+        let waits = requests
+            .windows(2)
+            .map(|pair| pair[1].received_at - pair[0].received_at)
+            .collect::<Vec<_>>();
+        for (wait, backoff) in waits.iter().zip([50, 100, 200]) {
+            assert!(*wait >= Duration::from_millis(backoff), "{:?}", waits);
+        }
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_retries() {
+        let (addr, requests) = stand_in(&[500]).await;
+        let result = notifier(format!("http://{}/", addr), None, 2)
+            .notify(&event())
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn retries_connection_errors() {
+        // Nothing listens on the port once the listener is gone
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let started = Instant::now();
+        let result = notifier(format!("http://{}/", addr), None, 2)
+            .notify(&event())
+            .await;
+
+        assert!(result.is_err());
+        assert!(started.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let (addr, requests) = stand_in(&[400, 200]).await;
+        let result = notifier(format!("http://{}/", addr), None, 3)
+            .notify(&event())
+            .await;
+
+        assert!(result.is_err());
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limits_are_retried() {
+        let (addr, requests) = stand_in(&[429, 200]).await;
+        notifier(format!("http://{}/", addr), None, 1)
+            .notify(&event())
+            .await
+            .unwrap();
+
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::db::entities::{check_result, incident, prelude::*};
+use crate::error::Result;
+use crate::monitor::CheckOutcome;
+use chrono::{NaiveDateTime, TimeDelta, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use std::time::Duration;
+
+/// Check intervals a result is taken to hold for at most. Longer gaps, e.g.
+/// while monitoring was stopped or paused, are left out of the report.
+const MAX_GAP_INTERVALS: u32 = 2;
+
+/// Availability over a period, derived from recorded check results. Every
+/// result is taken to hold until the next one, for `max_gap` at most.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UptimeReport {
+    /// Time covered by check results
+    pub observed: Duration,
+    pub downtime: Duration,
+    /// Times the server went down
+    pub incidents: u32,
+    /// Incidents that ended within the period and how long they took altogether
+    pub recoveries: u32,
+    pub recovery_time: Duration,
+}
+
+impl UptimeReport {
+    /// Builds the report for `from..to` out of results ordered by time. `before`
+    /// is the last result ahead of the period and gives the state it starts in,
+    /// unless it is more than `max_gap` older than the period.
+    pub fn from_results(
+        before: Option<&check_result::Model>,
+        results: &[check_result::Model],
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        max_gap: Duration,
+    ) -> Self {
+        let max_gap = TimeDelta::from_std(max_gap).unwrap_or(TimeDelta::MAX);
+        let mut report = Self::default();
+        let mut samples = before
+            .map(|result| (result.checked_at, result.is_up))
+            .into_iter()
+            .chain(
+                results
+                    .iter()
+                    .map(|result| (result.checked_at, result.is_up)),
+            )
+            .peekable();
+        let mut down_since = None;
+
+        while let Some((at, is_up)) = samples.next() {
+            let next = samples.peek().map_or(to, |&(next, _)| next);
+            let until = next.min(at.checked_add_signed(max_gap).unwrap_or(next));
+            let span = (until - at.max(from)).to_std().unwrap_or_default();
+            if span.is_zero() {
+                continue;
+            }
+            report.observed += span;
+
+            if !is_up {
+                report.downtime += span;
+                if down_since.is_none() {
+                    report.incidents += 1;
+                    down_since = Some(at.max(from));
+                }
+            } else if let Some(since) = down_since.take() {
+                report.recoveries += 1;
+                report.recovery_time += (at - since).to_std().unwrap_or_default();
+            }
+
+            // Nobody saw how a down spell went on after a gap, it does not add to MTTR
+            if until < next {
+                down_since = None;
+            }
+        }
+
+        report
+    }
+
+    /// Share of the observed time the server was up, in percent.
+    pub fn availability(&self) -> Option<f64> {
+        if self.observed.is_zero() {
+            return None;
+        }
+        let uptime = self.observed - self.downtime;
+        Some(uptime.as_secs_f64() / self.observed.as_secs_f64() * 100.0)
+    }
+
+    /// Mean time to recovery over the incidents that ended.
+    pub fn mttr(&self) -> Option<Duration> {
+        (self.recoveries > 0).then(|| self.recovery_time / self.recoveries)
+    }
+
+    /// Adds up the reports of several servers, e.g. for a group.
+    pub fn merge(&mut self, other: &Self) {
+        self.observed += other.observed;
+        self.downtime += other.downtime;
+        self.incidents += other.incidents;
+        self.recoveries += other.recoveries;
+        self.recovery_time += other.recovery_time;
+    }
+}
+
+#[derive(Clone)]
+pub struct CheckService {
+    db: DatabaseConnection,
+}
+
+impl CheckService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Records a probe, attached to the server's open incident if there is one.
+    pub async fn record_result(&self, server_id: i32, outcome: &CheckOutcome) -> Result<()> {
+        let incident = Incident::find()
+            .filter(incident::Column::ServerId.eq(server_id))
+            .filter(incident::Column::ResolvedAt.is_null())
+            .one(&self.db)
+            .await?;
+
+        let result = check_result::ActiveModel {
+            server_id: Set(server_id),
+            checked_at: Set(Utc::now().naive_utc()),
+            is_up: Set(outcome.is_up),
+            latency_ms: Set(outcome.latency.map(|latency| latency.as_millis() as i32)),
+            error_kind: Set(outcome.error),
+            incident_id: Set(incident.map(|incident| incident.id)),
+            ..Default::default()
+        };
+
+        CheckResult::insert(result).exec(&self.db).await?;
+        Ok(())
+    }
+
+    /// Uptime of a server checked every `interval`, gaps of more than a couple
+    /// of intervals count as unobserved.
+    pub async fn uptime(
+        &self,
+        server_id: i32,
+        interval: Duration,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<UptimeReport> {
+        let before = CheckResult::find()
+            .filter(check_result::Column::ServerId.eq(server_id))
+            .filter(check_result::Column::CheckedAt.lt(from))
+            .order_by_desc(check_result::Column::CheckedAt)
+            .one(&self.db)
+            .await?;
+
+        let results = CheckResult::find()
+            .filter(check_result::Column::ServerId.eq(server_id))
+            .filter(check_result::Column::CheckedAt.gte(from))
+            .filter(check_result::Column::CheckedAt.lt(to))
+            .order_by_asc(check_result::Column::CheckedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(UptimeReport::from_results(
+            before.as_ref(),
+            &results,
+            from,
+            to,
+            interval * MAX_GAP_INTERVALS,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn at(minutes: i64) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            + TimeDelta::minutes(minutes)
+    }
+
+    fn result(minutes: i64, is_up: bool) -> check_result::Model {
+        check_result::Model {
+            id: 0,
+            server_id: 1,
+            checked_at: at(minutes),
+            is_up,
+            latency_ms: None,
+            error_kind: None,
+            incident_id: None,
+        }
+    }
+
+    #[test]
+    fn results_hold_until_the_next_one() {
+        let results = [result(0, true), result(1, false), result(2, true)];
+        let report = UptimeReport::from_results(None, &results, at(0), at(3), 2 * MINUTE);
+        assert_eq!(report.observed, 3 * MINUTE);
+        assert_eq!(report.downtime, MINUTE);
+        assert_eq!((report.incidents, report.recoveries), (1, 1));
+        assert_eq!(report.mttr(), Some(MINUTE));
+    }
+
+    #[test]
+    fn gaps_are_not_observed() {
+        // Monitoring stopped while down at minute 1, resumed at minute 60
+        let results = [result(0, true), result(1, false), result(60, true)];
+        let report = UptimeReport::from_results(None, &results, at(0), at(61), 2 * MINUTE);
+        assert_eq!(report.observed, 4 * MINUTE);
+        assert_eq!(report.downtime, 2 * MINUTE);
+        assert_eq!((report.incidents, report.recoveries), (1, 0));
+        assert_eq!(report.mttr(), None);
+    }
+
+    #[test]
+    fn recovery_after_a_gap_is_timed_from_the_gap() {
+        let results = [result(0, false), result(60, false), result(61, true)];
+        let report = UptimeReport::from_results(None, &results, at(0), at(62), 2 * MINUTE);
+        assert_eq!(report.downtime, 3 * MINUTE);
+        assert_eq!((report.incidents, report.recoveries), (2, 1));
+        assert_eq!(report.mttr(), Some(MINUTE));
+    }
+
+    #[test]
+    fn last_result_does_not_run_to_the_end() {
+        let results = [result(0, false)];
+        let report = UptimeReport::from_results(None, &results, at(0), at(600), 2 * MINUTE);
+        assert_eq!(report.observed, 2 * MINUTE);
+        assert_eq!(report.downtime, 2 * MINUTE);
+    }
+
+    #[test]
+    fn recent_result_before_the_period_counts() {
+        let before = result(-1, false);
+        let results = [result(1, true)];
+        let report = UptimeReport::from_results(Some(&before), &results, at(0), at(2), 2 * MINUTE);
+        assert_eq!(report.observed, 2 * MINUTE);
+        assert_eq!(report.downtime, MINUTE);
+        assert_eq!(report.incidents, 1);
+    }
+
+    #[test]
+    fn stale_result_before_the_period_is_ignored() {
+        let before = result(-600, false);
+        let results = [result(10, true)];
+        let report = UptimeReport::from_results(Some(&before), &results, at(0), at(11), 2 * MINUTE);
+        assert_eq!(report.observed, MINUTE);
+        assert_eq!((report.downtime, report.incidents), (Duration::ZERO, 0));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::db::entities::server_group::{self, Entity as ServerGroup, Model as ServerGroupModel};
+use crate::db::entities::{server, server_group_member};
 use crate::error::Result;
+use chrono::Utc;
 use sea_orm::*;
 
 #[derive(Clone)]
@@ -12,9 +14,10 @@ impl GroupService {
         Self { db }
     }
 
-    pub async fn create_group(&self, name: String) -> Result<ServerGroupModel> {
+    pub async fn create_group(&self, owner_chat_id: i64, name: String) -> Result<ServerGroupModel> {
         let group = server_group::ActiveModel {
             name: Set(name),
+            owner_chat_id: Set(Some(owner_chat_id)),
             ..Default::default()
         };
 
@@ -25,8 +28,18 @@ impl GroupService {
         Ok(result)
     }
 
-    pub async fn list_groups(&self) -> Result<Vec<ServerGroupModel>> {
+    /// Looks up a group of the chat, groups of other chats are not found.
+    pub async fn get_group(&self, owner_chat_id: i64, id: i32) -> Result<Option<ServerGroupModel>> {
+        let group = ServerGroup::find_by_id(id)
+            .filter(server_group::Column::OwnerChatId.eq(owner_chat_id))
+            .one(&self.db)
+            .await?;
+        Ok(group)
+    }
+
+    pub async fn list_groups(&self, owner_chat_id: i64) -> Result<Vec<ServerGroupModel>> {
         let groups = ServerGroup::find()
+            .filter(server_group::Column::OwnerChatId.eq(owner_chat_id))
             .order_by_asc(server_group::Column::Name)
             .all(&self.db)
             .await?;
@@ -34,9 +47,158 @@ impl GroupService {
         Ok(groups)
     }
 
-    pub async fn delete_group(&self, id: i32) -> Result<bool> {
-        let result = ServerGroup::delete_by_id(id).exec(&self.db).await?;
+    pub async fn rename_group(
+        &self,
+        owner_chat_id: i64,
+        id: i32,
+        name: String,
+    ) -> Result<Option<ServerGroupModel>> {
+        let group = match self.get_group(owner_chat_id, id).await? {
+            Some(group) => group,
+            None => return Ok(None),
+        };
+
+        let mut group: server_group::ActiveModel = group.into();
+        group.name = Set(name);
+        group.updated_at = Set(Utc::now().naive_utc());
+        let group = group.update(&self.db).await?;
+
+        Ok(Some(group))
+    }
+
+    /// Sets the free text shown in `/group`, `None` clears it.
+    pub async fn set_description(
+        &self,
+        owner_chat_id: i64,
+        id: i32,
+        description: Option<String>,
+    ) -> Result<Option<ServerGroupModel>> {
+        let group = match self.get_group(owner_chat_id, id).await? {
+            Some(group) => group,
+            None => return Ok(None),
+        };
+
+        let mut group: server_group::ActiveModel = group.into();
+        group.description = Set(description);
+        group.updated_at = Set(Utc::now().naive_utc());
+        let group = group.update(&self.db).await?;
+
+        Ok(Some(group))
+    }
+
+    /// Groups of the chat the server belongs to.
+    pub async fn list_groups_of_server(
+        &self,
+        owner_chat_id: i64,
+        server_id: i32,
+    ) -> Result<Vec<ServerGroupModel>> {
+        let groups = ServerGroup::find()
+            .join(
+                JoinType::InnerJoin,
+                server_group::Relation::ServerGroupMember.def(),
+            )
+            .filter(server_group::Column::OwnerChatId.eq(owner_chat_id))
+            .filter(server_group_member::Column::ServerId.eq(server_id))
+            .all(&self.db)
+            .await?;
+
+        Ok(groups)
+    }
+
+    pub async fn delete_group(&self, owner_chat_id: i64, id: i32) -> Result<bool> {
+        let result = ServerGroup::delete_by_id(id)
+            .filter(server_group::Column::OwnerChatId.eq(owner_chat_id))
+            .exec(&self.db)
+            .await?;
 
         Ok(result.rows_affected > 0)
     }
+
+    /// Deletes a group together with its servers, also where they are in other
+    /// groups. Returns how many servers went, `None` when the group is not found.
+    pub async fn delete_group_with_servers(
+        &self,
+        owner_chat_id: i64,
+        id: i32,
+    ) -> Result<Option<u64>> {
+        let txn = self.db.begin().await?;
+
+        let group = ServerGroup::find_by_id(id)
+            .filter(server_group::Column::OwnerChatId.eq(owner_chat_id))
+            .one(&txn)
+            .await?;
+        if group.is_none() {
+            return Ok(None);
+        }
+
+        let server_ids = server_group_member::Entity::find()
+            .filter(server_group_member::Column::GroupId.eq(id))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|member| member.server_id)
+            .collect::<Vec<_>>();
+        let servers = server::Entity::delete_many()
+            .filter(server::Column::Id.is_in(server_ids))
+            .filter(server::Column::OwnerChatId.eq(owner_chat_id))
+            .exec(&txn)
+            .await?;
+        ServerGroup::delete_by_id(id).exec(&txn).await?;
+
+        txn.commit().await?;
+        Ok(Some(servers.rows_affected))
+    }
+
+    /// Sets where the group's alerts are POSTed to, `None` removes the webhook.
+    pub async fn set_webhook(
+        &self,
+        owner_chat_id: i64,
+        id: i32,
+        webhook: Option<(String, Option<String>)>,
+    ) -> Result<Option<ServerGroupModel>> {
+        let group = match self.get_group(owner_chat_id, id).await? {
+            Some(group) => group,
+            None => return Ok(None),
+        };
+
+        let (url, secret) = webhook.unzip();
+        let mut group: server_group::ActiveModel = group.into();
+        group.webhook_url = Set(url);
+        group.webhook_secret = Set(secret.flatten());
+        group.updated_at = Set(Utc::now().naive_utc());
+        let group = group.update(&self.db).await?;
+
+        Ok(Some(group))
+    }
+
+    /// Replaces the group's mail recipients, an empty list stops mail.
+    pub async fn set_email_recipients(
+        &self,
+        owner_chat_id: i64,
+        id: i32,
+        recipients: &[String],
+    ) -> Result<Option<ServerGroupModel>> {
+        let group = match self.get_group(owner_chat_id, id).await? {
+            Some(group) => group,
+            None => return Ok(None),
+        };
+
+        let mut group: server_group::ActiveModel = group.into();
+        group.email_recipients = Set((!recipients.is_empty()).then(|| recipients.join(",")));
+        group.updated_at = Set(Utc::now().naive_utc());
+        let group = group.update(&self.db).await?;
+
+        Ok(Some(group))
+    }
+
+    /// Hands groups created before ownership existed to `owner_chat_id`.
+    pub async fn adopt_unowned(&self, owner_chat_id: i64) -> Result<u64> {
+        let result = ServerGroup::update_many()
+            .col_expr(server_group::Column::OwnerChatId, owner_chat_id.into())
+            .filter(server_group::Column::OwnerChatId.is_null())
+            .exec(&self.db)
+            .await?;
+
+        Ok(result.rows_affected)
+    }
 }
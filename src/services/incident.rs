@@ -0,0 +1,130 @@
+use crate::db::entities::{check_result, incident, prelude::*};
+use crate::error::Result;
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+#[derive(Clone)]
+pub struct IncidentService {
+    db: DatabaseConnection,
+}
+
+impl IncidentService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Opens an incident for a server that went down, or returns the one that
+    /// is already open. The failed checks that led to it are attached.
+    pub async fn open(&self, server_id: i32, chat_id: i64) -> Result<incident::Model> {
+        if let Some(incident) = self.get_open(server_id).await? {
+            return Ok(incident);
+        }
+
+        let now = Utc::now().naive_utc();
+        let incident = incident::ActiveModel {
+            server_id: Set(server_id),
+            chat_id: Set(chat_id),
+            opened_at: Set(now),
+            last_alerted_at: Set(now),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await?;
+
+        let last_up = CheckResult::find()
+            .filter(check_result::Column::ServerId.eq(server_id))
+            .filter(check_result::Column::IsUp.eq(true))
+            .order_by_desc(check_result::Column::CheckedAt)
+            .one(&self.db)
+            .await?;
+
+        let mut streak = CheckResult::update_many()
+            .col_expr(check_result::Column::IncidentId, incident.id.into())
+            .filter(check_result::Column::ServerId.eq(server_id))
+            .filter(check_result::Column::IsUp.eq(false))
+            .filter(check_result::Column::IncidentId.is_null());
+        if let Some(last_up) = last_up {
+            streak = streak.filter(check_result::Column::CheckedAt.gt(last_up.checked_at));
+        }
+        streak.exec(&self.db).await?;
+
+        Ok(incident)
+    }
+
+    pub async fn get_open(&self, server_id: i32) -> Result<Option<incident::Model>> {
+        let incident = Incident::find()
+            .filter(incident::Column::ServerId.eq(server_id))
+            .filter(incident::Column::ResolvedAt.is_null())
+            .one(&self.db)
+            .await?;
+        Ok(incident)
+    }
+
+    pub async fn get_incident(&self, chat_id: i64, id: i32) -> Result<Option<incident::Model>> {
+        let incident = Incident::find_by_id(id)
+            .filter(incident::Column::ChatId.eq(chat_id))
+            .one(&self.db)
+            .await?;
+        Ok(incident)
+    }
+
+    pub async fn list_open(&self, chat_id: i64) -> Result<Vec<incident::Model>> {
+        let incidents = Incident::find()
+            .filter(incident::Column::ChatId.eq(chat_id))
+            .filter(incident::Column::ResolvedAt.is_null())
+            .order_by_asc(incident::Column::OpenedAt)
+            .all(&self.db)
+            .await?;
+        Ok(incidents)
+    }
+
+    /// Silences reminders of an open incident. Resolved or already acknowledged
+    /// incidents are returned unchanged.
+    pub async fn acknowledge(
+        &self,
+        chat_id: i64,
+        id: i32,
+        user_id: Option<i64>,
+    ) -> Result<Option<incident::Model>> {
+        let incident = match self.get_incident(chat_id, id).await? {
+            Some(incident) => incident,
+            None => return Ok(None),
+        };
+        if incident.resolved_at.is_some() || incident.acknowledged_at.is_some() {
+            return Ok(Some(incident));
+        }
+
+        let mut incident: incident::ActiveModel = incident.into();
+        incident.acknowledged_at = Set(Some(Utc::now().naive_utc()));
+        incident.acknowledged_by = Set(user_id);
+        let incident = incident.update(&self.db).await?;
+        Ok(Some(incident))
+    }
+
+    /// Closes the open incident of a server that recovered.
+    pub async fn resolve(&self, server_id: i32) -> Result<Option<incident::Model>> {
+        let incident = match self.get_open(server_id).await? {
+            Some(incident) => incident,
+            None => return Ok(None),
+        };
+
+        let mut incident: incident::ActiveModel = incident.into();
+        incident.resolved_at = Set(Some(Utc::now().naive_utc()));
+        let incident = incident.update(&self.db).await?;
+        Ok(Some(incident))
+    }
+
+    pub async fn set_alerted(&self, id: i32) -> Result<()> {
+        Incident::update_many()
+            .col_expr(
+                incident::Column::LastAlertedAt,
+                Utc::now().naive_utc().into(),
+            )
+            .filter(incident::Column::Id.eq(id))
+            .exec(&self.db)
+            .await?;
+        Ok(())
+    }
+}
@@ -1,2 +1,7 @@
+pub mod check;
 pub mod group;
+pub mod incident;
+pub mod role;
 pub mod server;
+pub mod settings;
+pub mod subscription;
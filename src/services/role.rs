@@ -0,0 +1,73 @@
+use crate::db::entities::{prelude::*, user_role};
+use crate::error::Result;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+
+#[derive(Clone)]
+pub struct RoleService {
+    db: DatabaseConnection,
+}
+
+impl RoleService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    pub async fn get_role(&self, user_id: i64) -> Result<Option<user_role::Role>> {
+        let role = UserRole::find()
+            .filter(user_role::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await?;
+        Ok(role.map(|role| role.role))
+    }
+
+    /// Gives a user a role, replacing the one they had.
+    pub async fn grant(
+        &self,
+        user_id: i64,
+        role: user_role::Role,
+        granted_by: Option<i64>,
+    ) -> Result<user_role::Model> {
+        let existing = UserRole::find()
+            .filter(user_role::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await?;
+
+        let user_role = match existing {
+            Some(user_role) => {
+                let mut user_role: user_role::ActiveModel = user_role.into();
+                user_role.role = Set(role);
+                user_role.granted_by = Set(granted_by);
+                user_role.update(&self.db).await?
+            }
+            None => {
+                let user_role = user_role::ActiveModel {
+                    user_id: Set(user_id),
+                    role: Set(role),
+                    granted_by: Set(granted_by),
+                    ..Default::default()
+                };
+                user_role.insert(&self.db).await?
+            }
+        };
+
+        Ok(user_role)
+    }
+
+    pub async fn revoke(&self, user_id: i64) -> Result<bool> {
+        let result = UserRole::delete_many()
+            .filter(user_role::Column::UserId.eq(user_id))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    pub async fn list_roles(&self) -> Result<Vec<user_role::Model>> {
+        let roles = UserRole::find()
+            .order_by_asc(user_role::Column::UserId)
+            .all(&self.db)
+            .await?;
+        Ok(roles)
+    }
+}
@@ -1,6 +1,160 @@
-use crate::db::entities::{prelude::*, server};
+use crate::db::entities::{prelude::*, server, server_group, server_group_member};
 use crate::error::Result;
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use crate::monitor::http::HttpCheck;
+use crate::monitor::tls::CertificateInfo;
+use chrono::Utc;
+use sea_orm::sea_query::{Expr, Func, OnConflict};
+use sea_orm::{
+    ActiveEnum, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, JoinType,
+    QueryFilter, QuerySelect, RelationTrait, Set,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Per-server overrides of the global check settings, `None` keeps the default.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckSettings {
+    pub interval: Option<i32>, // in seconds
+    pub timeout: Option<i32>,  // in seconds
+    pub retries: Option<i32>,
+    pub failures: Option<i32>,   // failed checks in a row before down
+    pub recoveries: Option<i32>, // successful checks in a row before up
+    #[serde(default)]
+    pub family: Option<server::AddressFamily>, // both when unset
+    #[serde(default)]
+    pub all_addresses: bool, // check every resolved address instead of the first that answers
+}
+
+impl CheckSettings {
+    pub fn from_server(server: &server::Model) -> Self {
+        Self {
+            interval: server.check_interval,
+            timeout: server.check_timeout,
+            retries: server.check_retries,
+            failures: server.failure_threshold,
+            recoveries: server.recovery_threshold,
+            family: server.address_family,
+            all_addresses: server.check_all_addresses,
+        }
+    }
+
+    /// Applies options like `interval=60 failures=3` on top of these settings.
+    /// A value of `default` drops that override, a lone `default` drops them all.
+    pub fn apply(mut self, input: &str) -> std::result::Result<Self, String> {
+        for token in input.split_whitespace() {
+            if token.eq_ignore_ascii_case("default") {
+                self = Self::default();
+                continue;
+            }
+
+            let (key, value) = token
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=value, got '{}'", token))?;
+            let (field, range) = match key.to_ascii_lowercase().as_str() {
+                "family" => {
+                    self.family = match value.to_ascii_lowercase().as_str() {
+                        "ipv4" | "4" => Some(server::AddressFamily::Ipv4),
+                        "ipv6" | "6" => Some(server::AddressFamily::Ipv6),
+                        "any" | "default" => None,
+                        _ => {
+                            return Err(format!(
+                                "family must be ipv4, ipv6 or any, not '{}'",
+                                value
+                            ))
+                        }
+                    };
+                    continue;
+                }
+                "addresses" => {
+                    self.all_addresses = match value.to_ascii_lowercase().as_str() {
+                        "all" => true,
+                        "any" | "default" => false,
+                        _ => return Err(format!("addresses must be all or any, not '{}'", value)),
+                    };
+                    continue;
+                }
+                "interval" => (&mut self.interval, 10..=86_400),
+                "timeout" => (&mut self.timeout, 1..=120),
+                "retries" => (&mut self.retries, 0..=10),
+                "failures" => (&mut self.failures, 1..=20),
+                "recoveries" => (&mut self.recoveries, 1..=20),
+                _ => return Err(format!("Unknown setting '{}'", key)),
+            };
+
+            *field = if value.eq_ignore_ascii_case("default") {
+                None
+            } else {
+                let parsed = value
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|parsed| range.contains(parsed))
+                    .ok_or_else(|| {
+                        format!(
+                            "{} must be between {} and {}",
+                            key,
+                            range.start(),
+                            range.end()
+                        )
+                    })?;
+                Some(parsed)
+            };
+        }
+
+        Ok(self)
+    }
+}
+
+impl fmt::Display for CheckSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: Option<i32>, unit: &str| {
+            value
+                .map(|value| format!("{}{}", value, unit))
+                .unwrap_or_else(|| "default".into())
+        };
+        write!(
+            f,
+            "interval {}, timeout {}, retries {}, failures {}, recoveries {}, family {}, addresses {}",
+            show(self.interval, "s"),
+            show(self.timeout, "s"),
+            show(self.retries, ""),
+            show(self.failures, ""),
+            show(self.recoveries, ""),
+            self.family.map_or("any".into(), |family| family.to_value()),
+            if self.all_addresses { "all" } else { "any" }
+        )
+    }
+}
+
+/// Everything needed to create a server.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NewServer {
+    pub name: String,
+    pub host: String,
+    pub port: i32,
+    pub check_kind: server::CheckKind,
+    pub http_check: Option<HttpCheck>,
+    pub settings: CheckSettings,
+}
+
+impl NewServer {
+    /// Current values of a server, as a starting point for editing it.
+    pub fn from_server(server: &server::Model) -> Self {
+        let http_check = matches!(
+            server.check_kind,
+            server::CheckKind::Http | server::CheckKind::Https
+        )
+        .then(|| HttpCheck::from_server(server));
+
+        Self {
+            name: server.name.clone(),
+            host: server.host.clone(),
+            port: server.port,
+            check_kind: server.check_kind,
+            http_check,
+            settings: CheckSettings::from_server(server),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct ServerService {
@@ -14,43 +168,95 @@ impl ServerService {
 
     pub async fn add_server(
         &self,
-        name: String,
-        host: String,
-        port: i32,
-        group_id: Option<i32>,
+        owner_chat_id: i64,
+        new_server: NewServer,
     ) -> Result<server::Model> {
-        let server = server::ActiveModel {
-            name: Set(name),
-            host: Set(host),
-            port: Set(port),
-            group_id: Set(group_id),
+        let mut server = server::ActiveModel {
+            owner_chat_id: Set(Some(owner_chat_id)),
+            name: Set(new_server.name),
+            host: Set(new_server.host),
+            port: Set(new_server.port),
+            check_kind: Set(new_server.check_kind),
+            check_interval: Set(new_server.settings.interval),
+            check_timeout: Set(new_server.settings.timeout),
+            check_retries: Set(new_server.settings.retries),
+            failure_threshold: Set(new_server.settings.failures),
+            recovery_threshold: Set(new_server.settings.recoveries),
+            address_family: Set(new_server.settings.family),
+            check_all_addresses: Set(new_server.settings.all_addresses),
             ..Default::default()
         };
 
+        if let Some(http_check) = new_server.http_check {
+            server.http_method = Set(Some(http_check.method));
+            server.http_path = Set(Some(http_check.path));
+            server.expected_status = Set(Some(http_check.expected_status.to_string()));
+            server.follow_redirects = Set(http_check.follow_redirects);
+        }
+
         let server = server.insert(&self.db).await?;
         Ok(server)
     }
 
-    pub async fn get_server(&self, id: i32) -> Result<Option<server::Model>> {
-        let server = Server::find_by_id(id).one(&self.db).await?;
+    /// Looks up a server of the chat, servers of other chats are not found.
+    pub async fn get_server(&self, owner_chat_id: i64, id: i32) -> Result<Option<server::Model>> {
+        let server = Server::find_by_id(id)
+            .filter(server::Column::OwnerChatId.eq(owner_chat_id))
+            .one(&self.db)
+            .await?;
+        Ok(server)
+    }
+
+    /// Looks up a server of the chat watching `host` and `port`, other than
+    /// `except`. Hostnames match regardless of case.
+    pub async fn find_by_address(
+        &self,
+        owner_chat_id: i64,
+        host: &str,
+        port: i32,
+        except: Option<i32>,
+    ) -> Result<Option<server::Model>> {
+        let mut query = Server::find()
+            .filter(server::Column::OwnerChatId.eq(owner_chat_id))
+            .filter(
+                Expr::expr(Func::lower(Expr::col(server::Column::Host))).eq(host.to_lowercase()),
+            )
+            .filter(server::Column::Port.eq(port));
+        if let Some(except) = except {
+            query = query.filter(server::Column::Id.ne(except));
+        }
+
+        let server = query.one(&self.db).await?;
         Ok(server)
     }
 
-    pub async fn list_servers(&self) -> Result<Vec<server::Model>> {
-        let servers = Server::find().all(&self.db).await?;
+    pub async fn list_servers(&self, owner_chat_id: i64) -> Result<Vec<server::Model>> {
+        let servers = Server::find()
+            .filter(server::Column::OwnerChatId.eq(owner_chat_id))
+            .all(&self.db)
+            .await?;
         Ok(servers)
     }
 
-    pub async fn list_servers_by_group(&self, group_id: i32) -> Result<Vec<server::Model>> {
+    pub async fn list_servers_by_group(
+        &self,
+        owner_chat_id: i64,
+        group_id: i32,
+    ) -> Result<Vec<server::Model>> {
         let servers = Server::find()
-            .filter(server::Column::GroupId.eq(Some(group_id)))
+            .join(
+                JoinType::InnerJoin,
+                server::Relation::ServerGroupMember.def(),
+            )
+            .filter(server::Column::OwnerChatId.eq(owner_chat_id))
+            .filter(server_group_member::Column::GroupId.eq(group_id))
             .all(&self.db)
             .await?;
         Ok(servers)
     }
 
-    pub async fn remove_server(&self, id: i32) -> Result<bool> {
-        let server = match self.get_server(id).await? {
+    pub async fn remove_server(&self, owner_chat_id: i64, id: i32) -> Result<bool> {
+        let server = match self.get_server(owner_chat_id, id).await? {
             Some(server) => server,
             None => return Ok(false),
         };
@@ -60,14 +266,170 @@ impl ServerService {
         Ok(true)
     }
 
-    pub async fn assign_to_group(&self, server_id: i32, group_id: i32) -> Result<bool> {
-        let server = match self.get_server(server_id).await? {
+    /// Adds a server to a group, both have to belong to the chat. Servers already
+    /// in the group stay as they are.
+    pub async fn add_to_group(
+        &self,
+        owner_chat_id: i64,
+        server_id: i32,
+        group_id: i32,
+    ) -> Result<bool> {
+        if self.get_server(owner_chat_id, server_id).await?.is_none() {
+            return Ok(false);
+        }
+
+        let group = ServerGroup::find_by_id(group_id)
+            .filter(server_group::Column::OwnerChatId.eq(owner_chat_id))
+            .one(&self.db)
+            .await?;
+        if group.is_none() {
+            return Ok(false);
+        }
+
+        let member = server_group_member::ActiveModel {
+            server_id: Set(server_id),
+            group_id: Set(group_id),
+            created_at: Set(Utc::now().naive_utc()),
+        };
+        ServerGroupMember::insert(member)
+            .on_conflict(
+                OnConflict::columns([
+                    server_group_member::Column::ServerId,
+                    server_group_member::Column::GroupId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(&self.db)
+            .await?;
+        Ok(true)
+    }
+
+    /// Takes a server of the chat out of a group, `false` when it was not in it.
+    pub async fn remove_from_group(
+        &self,
+        owner_chat_id: i64,
+        server_id: i32,
+        group_id: i32,
+    ) -> Result<bool> {
+        if self.get_server(owner_chat_id, server_id).await?.is_none() {
+            return Ok(false);
+        }
+
+        let result = ServerGroupMember::delete_by_id((server_id, group_id))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Replaces name, target, check and settings of a server, group membership
+    /// is left alone. A new target drops the status and certificate of the old one.
+    pub async fn update_server(
+        &self,
+        owner_chat_id: i64,
+        server_id: i32,
+        changes: NewServer,
+    ) -> Result<Option<server::Model>> {
+        let current = match self.get_server(owner_chat_id, server_id).await? {
+            Some(server) => server,
+            None => return Ok(None),
+        };
+        let retargeted = current.host != changes.host
+            || current.port != changes.port
+            || current.check_kind != changes.check_kind;
+
+        let mut server: server::ActiveModel = current.into();
+        server.name = Set(changes.name);
+        server.host = Set(changes.host);
+        server.port = Set(changes.port);
+        server.check_kind = Set(changes.check_kind);
+        let http_check = changes.http_check.unwrap_or_default();
+        let is_http = matches!(
+            changes.check_kind,
+            server::CheckKind::Http | server::CheckKind::Https
+        );
+        server.http_method = Set(is_http.then_some(http_check.method));
+        server.http_path = Set(is_http.then_some(http_check.path));
+        server.expected_status = Set(is_http.then(|| http_check.expected_status.to_string()));
+        server.follow_redirects = Set(is_http && http_check.follow_redirects);
+        server.check_interval = Set(changes.settings.interval);
+        server.check_timeout = Set(changes.settings.timeout);
+        server.check_retries = Set(changes.settings.retries);
+        server.failure_threshold = Set(changes.settings.failures);
+        server.recovery_threshold = Set(changes.settings.recoveries);
+        server.address_family = Set(changes.settings.family);
+        server.check_all_addresses = Set(changes.settings.all_addresses);
+        if retargeted {
+            server.last_status = Set(None);
+            server.cert_sni = Set(None);
+            server.cert_issuer = Set(None);
+            server.cert_not_after = Set(None);
+            server.cert_alerted_days = Set(None);
+        }
+        server.updated_at = Set(Utc::now().naive_utc());
+
+        let server = server.update(&self.db).await?;
+        Ok(Some(server))
+    }
+
+    /// Hands servers created before ownership existed to `owner_chat_id`.
+    pub async fn adopt_unowned(&self, owner_chat_id: i64) -> Result<u64> {
+        let result = Server::update_many()
+            .col_expr(server::Column::OwnerChatId, owner_chat_id.into())
+            .filter(server::Column::OwnerChatId.is_null())
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    // Bookkeeping of the checks below goes by ID alone, callers already hold a
+    // server they fetched for its owner.
+
+    pub async fn record_status(&self, server_id: i32, is_up: bool) -> Result<bool> {
+        let server = match Server::find_by_id(server_id).one(&self.db).await? {
+            Some(server) => server,
+            None => return Ok(false),
+        };
+
+        let mut server: server::ActiveModel = server.into();
+        server.last_status = Set(Some(is_up));
+        server.last_check = Set(Some(Utc::now().naive_utc()));
+        server.update(&self.db).await?;
+        Ok(true)
+    }
+
+    pub async fn record_certificate(
+        &self,
+        server_id: i32,
+        certificate: &CertificateInfo,
+    ) -> Result<bool> {
+        let server = match Server::find_by_id(server_id).one(&self.db).await? {
+            Some(server) => server,
+            None => return Ok(false),
+        };
+
+        // A renewed certificate starts over without any warnings sent
+        let renewed = server.cert_not_after != Some(certificate.not_after);
+
+        let mut server: server::ActiveModel = server.into();
+        server.cert_sni = Set(Some(certificate.sni.clone()));
+        server.cert_issuer = Set(Some(certificate.issuer.clone()));
+        server.cert_not_after = Set(Some(certificate.not_after));
+        if renewed {
+            server.cert_alerted_days = Set(None);
+        }
+        server.update(&self.db).await?;
+        Ok(true)
+    }
+
+    pub async fn set_cert_alerted_days(&self, server_id: i32, days: Option<i32>) -> Result<bool> {
+        let server = match Server::find_by_id(server_id).one(&self.db).await? {
             Some(server) => server,
             None => return Ok(false),
         };
 
         let mut server: server::ActiveModel = server.into();
-        server.group_id = Set(Some(group_id));
+        server.cert_alerted_days = Set(days);
         server.update(&self.db).await?;
         Ok(true)
     }
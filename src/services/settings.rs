@@ -0,0 +1,66 @@
+use crate::db::entities::{monitor_setting, prelude::*};
+use crate::error::Result;
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+
+#[derive(Clone)]
+pub struct SettingsService {
+    db: DatabaseConnection,
+}
+
+impl SettingsService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Interval stored for a chat, or the global one when `chat_id` is `None`.
+    pub async fn get_interval(&self, chat_id: Option<i64>) -> Result<Option<i32>> {
+        let setting = MonitorSetting::find()
+            .filter(Self::scope(chat_id))
+            .one(&self.db)
+            .await?;
+        Ok(setting.map(|setting| setting.check_interval))
+    }
+
+    pub async fn set_interval(&self, chat_id: Option<i64>, check_interval: i32) -> Result<()> {
+        let existing = MonitorSetting::find()
+            .filter(Self::scope(chat_id))
+            .one(&self.db)
+            .await?;
+
+        match existing {
+            Some(setting) => {
+                let mut setting: monitor_setting::ActiveModel = setting.into();
+                setting.check_interval = Set(check_interval);
+                setting.updated_at = Set(Utc::now().naive_utc());
+                setting.update(&self.db).await?;
+            }
+            None => {
+                let setting = monitor_setting::ActiveModel {
+                    chat_id: Set(chat_id),
+                    check_interval: Set(check_interval),
+                    updated_at: Set(Utc::now().naive_utc()),
+                    ..Default::default()
+                };
+                setting.insert(&self.db).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn clear_interval(&self, chat_id: Option<i64>) -> Result<bool> {
+        let result = MonitorSetting::delete_many()
+            .filter(Self::scope(chat_id))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    fn scope(chat_id: Option<i64>) -> sea_orm::sea_query::SimpleExpr {
+        match chat_id {
+            Some(chat_id) => monitor_setting::Column::ChatId.eq(chat_id),
+            None => monitor_setting::Column::ChatId.is_null(),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::db::entities::{monitor_subscription, prelude::*};
+use crate::error::Result;
+use crate::monitor::MonitorScope;
+use sea_orm::{
+    sea_query::SimpleExpr, ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, QueryOrder, Set,
+};
+
+#[derive(Clone)]
+pub struct SubscriptionService {
+    db: DatabaseConnection,
+}
+
+impl SubscriptionService {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self { db }
+    }
+
+    /// Records that a chat monitors a scope, replacing an earlier subscription.
+    pub async fn subscribe(
+        &self,
+        chat_id: i64,
+        scope: MonitorScope,
+        check_interval: Option<i32>,
+    ) -> Result<monitor_subscription::Model> {
+        let existing = MonitorSubscription::find()
+            .filter(monitor_subscription::Column::ChatId.eq(chat_id))
+            .filter(Self::scope(scope))
+            .one(&self.db)
+            .await?;
+
+        let subscription = match existing {
+            Some(subscription) => {
+                let mut subscription: monitor_subscription::ActiveModel = subscription.into();
+                subscription.check_interval = Set(check_interval);
+                subscription.paused = Set(false);
+                subscription.update(&self.db).await?
+            }
+            None => {
+                let subscription = monitor_subscription::ActiveModel {
+                    chat_id: Set(chat_id),
+                    group_id: Set(Self::group_id(scope)),
+                    check_interval: Set(check_interval),
+                    paused: Set(false),
+                    ..Default::default()
+                };
+                subscription.insert(&self.db).await?
+            }
+        };
+
+        Ok(subscription)
+    }
+
+    /// Drops the chat's subscription for `scope`, or all of them for `None`.
+    pub async fn unsubscribe(&self, chat_id: i64, scope: Option<MonitorScope>) -> Result<u64> {
+        let mut delete = MonitorSubscription::delete_many()
+            .filter(monitor_subscription::Column::ChatId.eq(chat_id));
+        if let Some(scope) = scope {
+            delete = delete.filter(Self::scope(scope));
+        }
+
+        let result = delete.exec(&self.db).await?;
+        Ok(result.rows_affected)
+    }
+
+    /// Pauses the chat's subscription for `scope`, or all of them for `None`.
+    pub async fn set_paused(
+        &self,
+        chat_id: i64,
+        scope: Option<MonitorScope>,
+        paused: bool,
+    ) -> Result<u64> {
+        let mut update = MonitorSubscription::update_many()
+            .col_expr(monitor_subscription::Column::Paused, paused.into())
+            .filter(monitor_subscription::Column::ChatId.eq(chat_id));
+        if let Some(scope) = scope {
+            update = update.filter(Self::scope(scope));
+        }
+
+        let result = update.exec(&self.db).await?;
+        Ok(result.rows_affected)
+    }
+
+    pub async fn list_subscriptions(&self) -> Result<Vec<monitor_subscription::Model>> {
+        let subscriptions = MonitorSubscription::find()
+            .order_by_asc(monitor_subscription::Column::Id)
+            .all(&self.db)
+            .await?;
+        Ok(subscriptions)
+    }
+
+    fn group_id(scope: MonitorScope) -> Option<i32> {
+        match scope {
+            MonitorScope::All => None,
+            MonitorScope::Group(group_id) => Some(group_id),
+        }
+    }
+
+    fn scope(scope: MonitorScope) -> SimpleExpr {
+        match scope {
+            MonitorScope::All => monitor_subscription::Column::GroupId.is_null(),
+            MonitorScope::Group(group_id) => monitor_subscription::Column::GroupId.eq(group_id),
+        }
+    }
+}